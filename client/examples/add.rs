@@ -1,14 +1,16 @@
 use arithmetic::add::Add;
-use client::{initialize_client, setup_payer, setup_program, ClientError, Config};
+use client::{
+    create_job, initialize_client, new_job_id, setup_payer, setup_program, simulator::load_job,
+    ClientError, Config,
+};
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
-    signature::{Keypair, Signer},
-    system_instruction,
+    signature::Signer,
     transaction::Transaction,
 };
-use std::{mem::size_of, path::Path};
-use utils::{AccountCast, BidirectionalStack, Executable};
-use verifier::{instruction::VerifierInstruction, state::BidirectionalStackAccount};
+use std::path::Path;
+use utils::{BidirectionalStack, Executable};
+use verifier::instruction::VerifierInstruction;
 
 /// Main entry point for the Solana program client
 fn main() -> client::Result<()> {
@@ -29,57 +31,15 @@ fn main() -> client::Result<()> {
 
     println!("Using program ID: {}", program_id);
 
-    // Create a new account that's owned by our program
-    let stack_account = Keypair::new();
-    println!("Creating new account: {}", stack_account.pubkey());
-
-    // Calculate the space needed for our account
-    let space = size_of::<BidirectionalStackAccount>();
-    println!("Account space: {} bytes", space);
-
-    // Create account instruction
-    let create_account_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &stack_account.pubkey(),
-        client.get_minimum_balance_for_rent_exemption(space)?,
-        space as u64,
-        &program_id,
-    );
-
-    // Create and send the transaction
-    let create_account_tx = Transaction::new_signed_with_payer(
-        &[create_account_ix],
-        Some(&payer.pubkey()),
-        &[&payer, &stack_account],
-        client.get_latest_blockhash()?,
-    );
-
-    let signature = client.send_and_confirm_transaction(&create_account_tx)?;
-    println!("Account created successfully: {}", signature);
-
-    // Initialize the account
-    let init_ix = Instruction::new_with_borsh(
-        program_id,
-        &VerifierInstruction::Initialize,
-        vec![AccountMeta::new(stack_account.pubkey(), false)],
-    );
-
-    // Send initialize transaction
-    let init_tx = Transaction::new_signed_with_payer(
-        &[init_ix],
-        Some(&payer.pubkey()),
-        &[&payer],
-        client.get_latest_blockhash()?,
-    );
-
-    let init_signature = client.send_and_confirm_transaction(&init_tx)?;
-    println!("Account initialized: {}", init_signature);
+    // Create the job account at the address derived from the payer and the job id
+    let job_id = config.job_id.unwrap_or_else(new_job_id);
+    let stack_account = create_job(&client, &payer, &program_id, job_id, config.stack_capacity)?;
 
     // Cast to stack account to see if initialized correctly
     let account_data_after_init = client
-        .get_account_data(&stack_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let stack = BidirectionalStackAccount::cast(&account_data_after_init);
+        .get_account_data(&stack_account)
+        .map_err(ClientError::from)?;
+    let stack = load_job(&account_data_after_init)?;
     println!("Stack front_index: {}", stack.front_index);
     println!("Stack back_index: {}", stack.back_index);
 
@@ -93,7 +53,10 @@ fn main() -> client::Result<()> {
     let push_task_ix = Instruction::new_with_borsh(
         program_id,
         &VerifierInstruction::PushTask(Add::new(48, 52).to_vec_with_type_tag()),
-        vec![AccountMeta::new(stack_account.pubkey(), false)],
+        vec![
+            AccountMeta::new(stack_account, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ],
     );
 
     let push_tx = Transaction::new_signed_with_payer(
@@ -108,9 +71,9 @@ fn main() -> client::Result<()> {
 
     // Check stack state after pushing
     let account_data_after_push = client
-        .get_account_data(&stack_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let stack_after_push = BidirectionalStackAccount::cast(&account_data_after_push);
+        .get_account_data(&stack_account)
+        .map_err(ClientError::from)?;
+    let stack_after_push = load_job(&account_data_after_push)?;
     println!("Stack front index: {}", stack_after_push.front_index);
     println!("Stack back index: {}", stack_after_push.back_index);
 
@@ -123,7 +86,7 @@ fn main() -> client::Result<()> {
         let execute_ix = Instruction::new_with_borsh(
             program_id,
             &VerifierInstruction::Execute,
-            vec![AccountMeta::new(stack_account.pubkey(), false)],
+            vec![AccountMeta::new(stack_account, false)],
         );
 
         let execute_tx = Transaction::new_signed_with_payer(
@@ -138,9 +101,9 @@ fn main() -> client::Result<()> {
 
         // Check final stack state
         let account_data = client
-            .get_account_data(&stack_account.pubkey())
-            .map_err(ClientError::SolanaClientError)?;
-        let stack = BidirectionalStackAccount::cast(&account_data);
+            .get_account_data(&stack_account)
+            .map_err(ClientError::from)?;
+        let stack = load_job(&account_data)?;
         println!("Stack front index: {}", stack.front_index);
         println!("Stack back index: {}", stack.back_index);
         println!("Executed task, is empty: {}", stack.is_empty_back());
@@ -151,11 +114,10 @@ fn main() -> client::Result<()> {
 
     // Read and display the result
     let account_data = client
-        .get_account_data(&stack_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let stack = BidirectionalStackAccount::cast(&account_data);
-    let result_bytes = stack.borrow_front();
-    let result = u128::from_be_bytes(result_bytes.try_into().unwrap());
+        .get_account_data(&stack_account)
+        .map_err(ClientError::from)?;
+    let stack = load_job(&account_data)?;
+    let result: u128 = stack.peek_value().unwrap();
     println!("\nAdd result (48 + 52): {}", result);
 
     println!("\nArithmetic operation successfully executed on Solana!");
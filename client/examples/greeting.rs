@@ -55,10 +55,11 @@ fn main() -> client::Result<()> {
     )?;
 
     println!("Greeting program interaction completed successfully!");
-    let mut account_data = client
+    let account_data = client
         .get_account_data(&greeting_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let greeting_account = GreetingAccount::cast_mut(&mut account_data);
+        .map_err(ClientError::from)?;
+    let greeting_account = GreetingAccount::cast(&account_data)
+        .map_err(|e| ClientError::SerializationError(e.to_string()))?;
     println!("Greeting counter: {}", greeting_account.counter);
     println!(
         "Greeting double counter: {}",
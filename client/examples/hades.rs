@@ -1,15 +1,23 @@
-use client::{initialize_client, setup_payer, setup_program, ClientError, Config};
+use client::{
+    create_job, finalize, initialize_client, job_completed, new_job_id, setup_payer, setup_program,
+    simulator::load_job, ClientError, Config, TaskManifest,
+};
 use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
     instruction::{AccountMeta, Instruction},
-    signature::{Keypair, Signer},
-    system_instruction,
+    signature::Signer,
     transaction::Transaction,
 };
 use stark::felt::Felt;
 use stark::poseidon::hades::HadesPermutation;
-use std::{mem::size_of, path::Path};
-use utils::{AccountCast, BidirectionalStack, Executable};
-use verifier::{instruction::VerifierInstruction, state::BidirectionalStackAccount};
+use std::path::Path;
+use utils::{Executable, StackValue};
+use verifier::instruction::VerifierInstruction;
+
+/// Compute units requested for each execute transaction
+const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+/// Compute units kept in reserve so the last step started always completes
+const COMPUTE_MARGIN: u64 = 100_000;
 
 /// Main entry point for the Solana program client
 fn main() -> client::Result<()> {
@@ -30,57 +38,15 @@ fn main() -> client::Result<()> {
 
     println!("Using program ID: {}", program_id);
 
-    // Create a new account that's owned by our program
-    let stack_account = Keypair::new();
-    println!("Creating new account: {}", stack_account.pubkey());
-
-    // Calculate the space needed for our account
-    let space = size_of::<BidirectionalStackAccount>();
-    println!("Account space: {} bytes", space);
-
-    // Create account instruction
-    let create_account_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &stack_account.pubkey(),
-        client.get_minimum_balance_for_rent_exemption(space)?,
-        space as u64,
-        &program_id,
-    );
-
-    // Create and send the transaction
-    let create_account_tx = Transaction::new_signed_with_payer(
-        &[create_account_ix],
-        Some(&payer.pubkey()),
-        &[&payer, &stack_account],
-        client.get_latest_blockhash()?,
-    );
-
-    let signature = client.send_and_confirm_transaction(&create_account_tx)?;
-    println!("Account created successfully: {}", signature);
-
-    // Initialize the account
-    let init_ix = Instruction::new_with_borsh(
-        program_id,
-        &VerifierInstruction::Initialize,
-        vec![AccountMeta::new(stack_account.pubkey(), false)],
-    );
-
-    // Send initialize transaction
-    let init_tx = Transaction::new_signed_with_payer(
-        &[init_ix],
-        Some(&payer.pubkey()),
-        &[&payer],
-        client.get_latest_blockhash()?,
-    );
-
-    let init_signature = client.send_and_confirm_transaction(&init_tx)?;
-    println!("Account initialized: {}", init_signature);
+    // Create the job account at the address derived from the payer and the job id
+    let job_id = config.job_id.unwrap_or_else(new_job_id);
+    let stack_account = create_job(&client, &payer, &program_id, job_id, config.stack_capacity)?;
 
     // Cast to stack account to see if initialized correctly
     let account_data_after_init = client
-        .get_account_data(&stack_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let stack = BidirectionalStackAccount::cast(&account_data_after_init);
+        .get_account_data(&stack_account)
+        .map_err(ClientError::from)?;
+    let stack = load_job(&account_data_after_init)?;
     println!("Stack front_index: {}", stack.front_index);
     println!("Stack back_index: {}", stack.back_index);
 
@@ -104,7 +70,10 @@ fn main() -> client::Result<()> {
     let push_task_ix = Instruction::new_with_borsh(
         program_id,
         &VerifierInstruction::PushTask(HadesPermutation::new(state).to_vec_with_type_tag()),
-        vec![AccountMeta::new(stack_account.pubkey(), false)],
+        vec![
+            AccountMeta::new(stack_account, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ],
     );
 
     let push_tx = Transaction::new_signed_with_payer(
@@ -118,50 +87,52 @@ fn main() -> client::Result<()> {
     println!("\nHades task pushed: {}", push_signature);
 
     // Execute until task is complete
-    let mut steps = 0;
-    loop {
-        // Execute the task
+    let manifest = TaskManifest::builtin()?;
+    let mut transactions = 0;
+    let completed = loop {
+        // Execute as many steps as fit in the compute budget of one transaction
+        let compute_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNITS);
         let execute_ix = Instruction::new_with_borsh(
             program_id,
-            &VerifierInstruction::Execute,
-            vec![AccountMeta::new(stack_account.pubkey(), false)],
+            &VerifierInstruction::ExecuteMany {
+                max_steps: u32::MAX,
+                compute_margin: COMPUTE_MARGIN,
+            },
+            vec![AccountMeta::new(stack_account, false)],
         );
 
         let execute_tx = Transaction::new_signed_with_payer(
-            &[execute_ix],
+            &[compute_limit_ix, execute_ix],
             Some(&payer.pubkey()),
             &[&payer],
             client.get_latest_blockhash()?,
         );
 
-        let _execute_signature = client.send_and_confirm_transaction(&execute_tx)?;
-        println!(".");
-        steps += 1;
+        let execute_signature = client.send_and_confirm_transaction(&execute_tx)?;
+        transactions += 1;
+
+        // The transaction that finishes the job logs its result
+        if let Some(completed) = job_completed(&client, &execute_signature)? {
+            println!("\nExecution complete after {} transactions", transactions);
+            break completed;
+        }
 
         // Check stack state
         let account_data = client
-            .get_account_data(&stack_account.pubkey())
-            .map_err(ClientError::SolanaClientError)?;
-        let stack = BidirectionalStackAccount::cast(&account_data);
-        if stack.is_empty_back() {
-            println!("\nExecution complete after {} steps", steps);
-            break;
-        }
-    }
-
-    // Read and display the result
-    let mut account_data = client
-        .get_account_data(&stack_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let stack = BidirectionalStackAccount::cast_mut(&mut account_data);
-    let result_bytes = stack.borrow_front();
-    let result = Felt::from_bytes_be(&result_bytes.try_into().unwrap());
-    stack.pop_front();
-    stack.pop_front();
-    stack.pop_front();
+            .get_account_data(&stack_account)
+            .map_err(ClientError::from)?;
+        let stack = load_job(&account_data)?;
+        println!("Pending tasks: {:?}", manifest.pending_tasks(&stack)?);
+    };
+
+    // Display the result carried by the completion event
+    let frames = completed
+        .frames
+        .ok_or_else(|| ClientError::SerializationError("Result too large".to_string()))?;
+    let [result, _, _] = <[Felt; 3]>::from_frame(&frames[0])
+        .map_err(|e| ClientError::SerializationError(e.to_string()))?;
     println!("\nHades permutation result: {}", result);
-    println!("Stack front index: {}", stack.front_index);
-    println!("Stack back index: {}", stack.back_index);
+    println!("Steps executed: {}", completed.steps);
 
     // The expected output should match the result we got
     let expected_result =
@@ -171,5 +142,9 @@ fn main() -> client::Result<()> {
     assert_eq!(result, expected_result);
     println!("\nHades permutation successfully executed on Solana!");
 
+    // Keep the outcome in the result account and reclaim the job account
+    let result_account = finalize(&client, &payer, &program_id, &stack_account)?;
+    println!("Verification result stored in {}", result_account);
+
     Ok(())
 }
@@ -1,15 +1,23 @@
-use client::{initialize_client, setup_payer, setup_program, ClientError, Config};
+use client::{
+    create_job, initialize_client, new_job_id, push_data, setup_payer, setup_program,
+    simulator::load_job, ClientError, Config,
+};
 use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
     instruction::{AccountMeta, Instruction},
-    signature::{Keypair, Signer},
-    system_instruction,
+    signature::Signer,
     transaction::Transaction,
 };
 use stark::felt::Felt;
 use stark::poseidon::PoseidonHashMany;
-use std::{mem::size_of, path::Path};
-use utils::{AccountCast, BidirectionalStack, Executable};
-use verifier::{instruction::VerifierInstruction, state::BidirectionalStackAccount};
+use std::path::Path;
+use utils::{BidirectionalStack, Executable, StackValue};
+use verifier::instruction::VerifierInstruction;
+
+/// Compute units requested for each execute transaction
+const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+/// Compute units kept in reserve so the last step started always completes
+const COMPUTE_MARGIN: u64 = 100_000;
 
 /// Main entry point for the Solana program client
 fn main() -> client::Result<()> {
@@ -30,57 +38,15 @@ fn main() -> client::Result<()> {
 
     println!("Using program ID: {}", program_id);
 
-    // Create a new account that's owned by our program
-    let stack_account = Keypair::new();
-    println!("Creating new account: {}", stack_account.pubkey());
-
-    // Calculate the space needed for our account
-    let space = size_of::<BidirectionalStackAccount>();
-    println!("Account space: {} bytes", space);
-
-    // Create account instruction
-    let create_account_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &stack_account.pubkey(),
-        client.get_minimum_balance_for_rent_exemption(space)?,
-        space as u64,
-        &program_id,
-    );
-
-    // Create and send the transaction
-    let create_account_tx = Transaction::new_signed_with_payer(
-        &[create_account_ix],
-        Some(&payer.pubkey()),
-        &[&payer, &stack_account],
-        client.get_latest_blockhash()?,
-    );
-
-    let signature = client.send_and_confirm_transaction(&create_account_tx)?;
-    println!("Account created successfully: {}", signature);
-
-    // Initialize the account
-    let init_ix = Instruction::new_with_borsh(
-        program_id,
-        &VerifierInstruction::Initialize,
-        vec![AccountMeta::new(stack_account.pubkey(), false)],
-    );
-
-    // Send initialize transaction
-    let init_tx = Transaction::new_signed_with_payer(
-        &[init_ix],
-        Some(&payer.pubkey()),
-        &[&payer],
-        client.get_latest_blockhash()?,
-    );
-
-    let init_signature = client.send_and_confirm_transaction(&init_tx)?;
-    println!("Account initialized: {}", init_signature);
+    // Create the job account at the address derived from the payer and the job id
+    let job_id = config.job_id.unwrap_or_else(new_job_id);
+    let stack_account = create_job(&client, &payer, &program_id, job_id, config.stack_capacity)?;
 
     // Cast to stack account to see if initialized correctly
     let account_data_after_init = client
-        .get_account_data(&stack_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let stack = BidirectionalStackAccount::cast(&account_data_after_init);
+        .get_account_data(&stack_account)
+        .map_err(ClientError::from)?;
+    let stack = load_job(&account_data_after_init)?;
     println!("Stack front_index: {}", stack.front_index);
     println!("Stack back_index: {}", stack.back_index);
 
@@ -110,47 +76,24 @@ fn main() -> client::Result<()> {
     // 1. Pad inputs with 1 followed by 0's if necessary to make even length
     let mut padded_inputs = inputs.clone();
     padded_inputs.push(Felt::ONE);
-    padded_inputs.resize((padded_inputs.len() + 1) / 2 * 2, Felt::ZERO);
+    padded_inputs.resize(padded_inputs.len().div_ceil(2) * 2, Felt::ZERO);
 
     println!("Padded input length: {}", padded_inputs.len());
 
-    // 2. Push values in reverse order
-    for input in padded_inputs.iter().rev() {
-        let push_data_ix = Instruction::new_with_borsh(
-            program_id,
-            &VerifierInstruction::PushData(input.to_bytes_be().to_vec()),
-            vec![AccountMeta::new(stack_account.pubkey(), false)],
-        );
+    // 2. Push pairs of values in reverse order, topped by the initial zero state
+    let mut frames: Vec<Vec<u8>> = padded_inputs
+        .chunks_exact(2)
+        .rev()
+        .map(|pair| [pair[0], pair[1]].to_frame())
+        .collect();
+    frames.push([Felt::ZERO; 3].to_frame());
 
-        let push_data_tx = Transaction::new_signed_with_payer(
-            &[push_data_ix],
-            Some(&payer.pubkey()),
-            &[&payer],
-            client.get_latest_blockhash()?,
-        );
-
-        let push_data_sig = client.send_and_confirm_transaction(&push_data_tx)?;
-        println!("Pushed input value: {}", input);
-    }
-
-    // 3. Push three zeros
-    for _ in 0..3 {
-        let push_data_ix = Instruction::new_with_borsh(
-            program_id,
-            &VerifierInstruction::PushData(Felt::ZERO.to_bytes_be().to_vec()),
-            vec![AccountMeta::new(stack_account.pubkey(), false)],
-        );
-
-        let push_data_tx = Transaction::new_signed_with_payer(
-            &[push_data_ix],
-            Some(&payer.pubkey()),
-            &[&payer],
-            client.get_latest_blockhash()?,
-        );
-
-        let push_data_sig = client.send_and_confirm_transaction(&push_data_tx)?;
-        println!("Pushed zero value");
-    }
+    let signatures = push_data(&client, &payer, &program_id, &stack_account, &frames)?;
+    println!(
+        "Pushed {} data frames in {} transactions",
+        frames.len(),
+        signatures.len()
+    );
 
     let poseidon_task = PoseidonHashMany::new(&inputs);
 
@@ -158,7 +101,10 @@ fn main() -> client::Result<()> {
     let push_task_ix = Instruction::new_with_borsh(
         program_id,
         &VerifierInstruction::PushTask(poseidon_task.to_vec_with_type_tag()),
-        vec![AccountMeta::new(stack_account.pubkey(), false)],
+        vec![
+            AccountMeta::new(stack_account, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ],
     );
 
     let push_tx = Transaction::new_signed_with_payer(
@@ -172,17 +118,21 @@ fn main() -> client::Result<()> {
     println!("\nPoseidon hash task pushed: {}", push_signature);
 
     // Execute until task is complete
-    let mut steps = 0;
+    let mut transactions = 0;
     loop {
-        // Execute the task
+        // Execute as many steps as fit in the compute budget of one transaction
+        let compute_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNITS);
         let execute_ix = Instruction::new_with_borsh(
             program_id,
-            &VerifierInstruction::Execute,
-            vec![AccountMeta::new(stack_account.pubkey(), false)],
+            &VerifierInstruction::ExecuteMany {
+                max_steps: u32::MAX,
+                compute_margin: COMPUTE_MARGIN,
+            },
+            vec![AccountMeta::new(stack_account, false)],
         );
 
         let execute_tx = Transaction::new_signed_with_payer(
-            &[execute_ix],
+            &[compute_limit_ix, execute_ix],
             Some(&payer.pubkey()),
             &[&payer],
             client.get_latest_blockhash()?,
@@ -190,29 +140,25 @@ fn main() -> client::Result<()> {
 
         let _execute_signature = client.send_and_confirm_transaction(&execute_tx)?;
         println!(".");
-        steps += 1;
+        transactions += 1;
 
         // Check stack state
         let account_data = client
-            .get_account_data(&stack_account.pubkey())
-            .map_err(ClientError::SolanaClientError)?;
-        let stack = BidirectionalStackAccount::cast(&account_data);
+            .get_account_data(&stack_account)
+            .map_err(ClientError::from)?;
+        let stack = load_job(&account_data)?;
         if stack.is_empty_back() {
-            println!("\nExecution complete after {} steps", steps);
+            println!("\nExecution complete after {} transactions", transactions);
             break;
         }
     }
 
     // Read and display the result
-    let mut account_data = client
-        .get_account_data(&stack_account.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
-    let stack = BidirectionalStackAccount::cast_mut(&mut account_data);
-    let result_bytes = stack.borrow_front();
-    let result = Felt::from_bytes_be_slice(result_bytes);
-    stack.pop_front();
-    stack.pop_front();
-    stack.pop_front();
+    let account_data = client
+        .get_account_data(&stack_account)
+        .map_err(ClientError::from)?;
+    let mut stack = load_job(&account_data)?;
+    let [result, _, _]: [Felt; 3] = stack.pop_value().unwrap();
     println!("\nPoseidon hash result: {}", result);
     println!("Stack front index: {}", stack.front_index);
     println!("Stack back index: {}", stack.back_index);
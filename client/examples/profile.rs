@@ -0,0 +1,279 @@
+use arithmetic::{exp::Exp, fact::Factorial, fib::Fibonacci, mul::Mul};
+use client::{simulator::load_job, ClientError, TaskManifest};
+use litesvm::LiteSVM;
+use serde_json::json;
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use stark::blake2s::{Blake2s, Blake2sOutput};
+use stark::felt::Felt;
+use stark::keccak::{KeccakBytes, KeccakFelts, KeccakOutput};
+use stark::pedersen::PedersenHashOnElements;
+use stark::poseidon::{PoseidonHash, PoseidonHashMany, PoseidonHashSingle};
+use std::collections::BTreeMap;
+use std::{fs, path::Path};
+use utils::{BidirectionalStack, Scheduler};
+use verifier::{
+    error::VerifierError,
+    instruction::VerifierInstruction,
+    state::{AccountHeader, BidirectionalStackAccount},
+};
+
+/// Compute units requested for each execute transaction
+const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+/// Buffer capacity of the profiled verifier accounts
+const STACK_CAPACITY: usize = 64 * 1024;
+
+/// Runs verifier jobs on the built program in an in-process SVM, one step per transaction
+struct Profiler {
+    svm: LiteSVM,
+    payer: Keypair,
+    program_id: Pubkey,
+    manifest: TaskManifest,
+    /// Compute units of a transaction holding only the compute budget instruction
+    overhead: u64,
+    /// Compute units of every step, by the task that ran
+    samples: BTreeMap<String, Vec<u64>>,
+}
+
+impl Profiler {
+    fn new(program_path: &Path) -> client::Result<Self> {
+        let mut svm = LiteSVM::new();
+        let program_id = Pubkey::new_unique();
+        svm.add_program_from_file(program_id, program_path)?;
+
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 10_000_000_000)
+            .map_err(|e| ClientError::TransactionError(format!("Airdrop failed: {:?}", e.err)))?;
+
+        let mut profiler = Self {
+            svm,
+            payer,
+            program_id,
+            manifest: TaskManifest::builtin()?,
+            overhead: 0,
+            samples: BTreeMap::new(),
+        };
+        profiler.overhead = profiler.send(&[])?;
+        Ok(profiler)
+    }
+
+    /// Send one transaction and return the compute units it consumed
+    fn send(&mut self, instructions: &[Instruction]) -> client::Result<u64> {
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            MAX_COMPUTE_UNITS,
+        )];
+        all.extend_from_slice(instructions);
+
+        // Identical transactions would be rejected as already processed
+        self.svm.expire_blockhash();
+        let transaction = Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        let meta = self.svm.send_transaction(transaction).map_err(|e| {
+            ClientError::TransactionError(format!("{:?}\n{}", e.err, e.meta.pretty_logs()))
+        })?;
+        Ok(meta.compute_units_consumed)
+    }
+
+    fn instruction(&self, account: &Pubkey, instruction: &VerifierInstruction) -> Instruction {
+        Instruction::new_with_borsh(
+            self.program_id,
+            instruction,
+            vec![
+                AccountMeta::new(*account, false),
+                AccountMeta::new_readonly(self.payer.pubkey(), true),
+            ],
+        )
+    }
+
+    /// Queue a job prepared on a host stack and execute it step by step
+    fn run(&mut self, name: &str, job: &BidirectionalStackAccount) -> client::Result<()> {
+        let account = Pubkey::new_unique();
+        let space = AccountHeader::account_space(STACK_CAPACITY);
+        self.svm
+            .set_account(
+                account,
+                Account {
+                    lamports: self.svm.minimum_balance_for_rent_exemption(space),
+                    data: vec![0; space],
+                    owner: self.program_id,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+
+        // Rebuild the job in the account, bottom frames first
+        let data = bottom_up(job.front_frames())?;
+        let tasks = bottom_up(job.back_frames())?;
+        self.send(&[
+            self.instruction(&account, &VerifierInstruction::Initialize),
+            self.instruction(&account, &VerifierInstruction::PushDataBatch(data)),
+            self.instruction(&account, &VerifierInstruction::PushTaskBatch(tasks)),
+        ])?;
+
+        let mut steps = 0;
+        loop {
+            let account_data = self.svm.get_account(&account).unwrap_or_default().data;
+            let stack = load_job(&account_data)?;
+            let task = match stack.borrow_back() {
+                Ok(frame) => self.manifest.describe(frame),
+                Err(_) => break,
+            };
+
+            let units = self.send(&[Instruction::new_with_borsh(
+                self.program_id,
+                &VerifierInstruction::Execute,
+                vec![AccountMeta::new(account, false)],
+            )])?;
+            self.samples
+                .entry(task)
+                .or_default()
+                .push(units.saturating_sub(self.overhead));
+            steps += 1;
+        }
+        println!("Profiled {} in {} steps", name, steps);
+
+        Ok(())
+    }
+}
+
+/// Copy the frames of one side of a host stack, from the bottom upwards
+fn bottom_up<'a>(
+    frames: impl Iterator<Item = Result<&'a [u8], VerifierError>>,
+) -> client::Result<Vec<Vec<u8>>> {
+    let mut frames = frames
+        .map(|frame| frame.map(<[u8]>::to_vec))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ClientError::SerializationError(e.to_string()))?;
+    frames.reverse();
+    Ok(frames)
+}
+
+/// Host stack holding a job, for its frames to be pushed into an account
+fn job(
+    queue: impl FnOnce(&mut BidirectionalStackAccount) -> Result<(), VerifierError>,
+) -> client::Result<Box<BidirectionalStackAccount>> {
+    let mut stack = BidirectionalStackAccount::with_capacity(STACK_CAPACITY);
+    queue(&mut stack).map_err(|e| ClientError::SerializationError(e.to_string()))?;
+    Ok(stack)
+}
+
+/// Profile the compute units of every task type on the built verifier program
+fn main() -> client::Result<()> {
+    let mut profiler = Profiler::new(Path::new("target/deploy/verifier.so"))?;
+
+    let inputs: Vec<Felt> = (1..=8u64).map(Felt::from).collect();
+    profiler.run(
+        "poseidon hash of 8 inputs",
+        &job(|stack| {
+            PoseidonHashMany::push_input(&inputs, stack)?;
+            stack.push_task(PoseidonHashMany::new(&inputs))
+        })?,
+    )?;
+    profiler.run(
+        "poseidon hash of a pair",
+        &job(|stack| {
+            PoseidonHash::push_input(inputs[0], inputs[1], stack)?;
+            stack.push_task(PoseidonHash::new())
+        })?,
+    )?;
+    profiler.run(
+        "poseidon hash of a single input",
+        &job(|stack| {
+            PoseidonHashSingle::push_input(inputs[0], stack)?;
+            stack.push_task(PoseidonHashSingle::new())
+        })?,
+    )?;
+    profiler.run(
+        "pedersen hash of 3 elements",
+        &job(|stack| {
+            PedersenHashOnElements::push_input(&inputs[..3], stack)?;
+            stack.push_task(PedersenHashOnElements::new(&inputs[..3]))
+        })?,
+    )?;
+    let bytes: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 136]).collect();
+    profiler.run(
+        "keccak of 4 byte frames",
+        &job(|stack| {
+            KeccakBytes::push_input(&bytes, stack)?;
+            stack.push_task(KeccakBytes::new(&bytes, KeccakOutput::Full))
+        })?,
+    )?;
+    profiler.run(
+        "keccak of 8 felts",
+        &job(|stack| {
+            KeccakFelts::push_input(&inputs, stack)?;
+            stack.push_task(KeccakFelts::new(&inputs, KeccakOutput::Masked160Lsb))
+        })?,
+    )?;
+    let message: Vec<u8> = (0..3 * Blake2s::CHUNK_BYTES).map(|i| i as u8).collect();
+    profiler.run(
+        "blake2s of 3 chunks",
+        &job(|stack| {
+            Blake2s::push_bytes(&message, stack)?;
+            stack.push_task(Blake2s::new_bytes(&message, Blake2sOutput::Full))
+        })?,
+    )?;
+    profiler.run(
+        "blake2s of 8 felts",
+        &job(|stack| {
+            Blake2s::push_input(&inputs, stack)?;
+            stack.push_task(Blake2s::new(&inputs, Blake2sOutput::Masked248Lsb))
+        })?,
+    )?;
+    profiler.run("mul", &job(|stack| stack.push_task(Mul::new(7, 9)))?)?;
+    profiler.run("exp", &job(|stack| stack.push_task(Exp::new(3, 8)))?)?;
+    profiler.run(
+        "factorial",
+        &job(|stack| stack.push_task(Factorial::new(10)))?,
+    )?;
+    profiler.run(
+        "fibonacci",
+        &job(|stack| stack.push_task(Fibonacci::new(8)))?,
+    )?;
+
+    println!(
+        "\n{:<50} {:>6} {:>9} {:>9} {:>9}",
+        "task", "steps", "min", "max", "mean"
+    );
+    let mut tasks = Vec::new();
+    for (task, samples) in &profiler.samples {
+        let min = samples.iter().min().copied().unwrap_or_default();
+        let max = samples.iter().max().copied().unwrap_or_default();
+        let mean = samples.iter().sum::<u64>() / samples.len() as u64;
+        println!(
+            "{:<50} {:>6} {:>9} {:>9} {:>9}",
+            task,
+            samples.len(),
+            min,
+            max,
+            mean
+        );
+        tasks.push(json!({
+            "task": task,
+            "steps": samples.len(),
+            "min": min,
+            "max": max,
+            "mean": mean,
+        }));
+    }
+
+    let profile_path = Path::new("target/deploy/verifier-profile.json");
+    fs::write(
+        profile_path,
+        serde_json::to_string_pretty(&json!({ "tasks": tasks }))?,
+    )?;
+    println!("\nSaved profile to {}", profile_path.display());
+
+    Ok(())
+}
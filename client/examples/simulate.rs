@@ -0,0 +1,32 @@
+use client::{simulator::simulate, ClientError, TaskManifest};
+use stark::felt::Felt;
+use stark::poseidon::PoseidonHashMany;
+use std::{fs, path::Path};
+use utils::{BidirectionalStack, Scheduler};
+use verifier::state::BidirectionalStackAccount;
+
+/// Run a Poseidon hash job on the host and save its trace
+fn main() -> client::Result<()> {
+    let inputs = [Felt::ONE, Felt::TWO, Felt::THREE];
+
+    let mut stack = BidirectionalStackAccount::with_capacity(64 * 1024);
+    PoseidonHashMany::push_input(&inputs, stack.as_mut())
+        .map_err(|e| ClientError::SerializationError(e.to_string()))?;
+    stack
+        .push_task(PoseidonHashMany::new(&inputs))
+        .map_err(|e| ClientError::SerializationError(e.to_string()))?;
+
+    let trace = simulate(&mut stack, &TaskManifest::builtin()?, usize::MAX);
+    trace.print();
+
+    let [result, _, _]: [Felt; 3] = stack
+        .peek_value()
+        .map_err(|e| ClientError::SerializationError(e.to_string()))?;
+    println!("Poseidon hash result: {}", result);
+
+    let trace_path = Path::new("target/poseidon-trace.json");
+    fs::write(trace_path, serde_json::to_string_pretty(&trace.to_json())?)?;
+    println!("Saved trace to {}", trace_path.display());
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use client::TaskManifest;
+use std::{fs, path::Path};
+use verifier::scheduler::TASK_MANIFEST;
+
+/// Save the task manifest next to the verifier program for off-chain tools
+fn main() -> client::Result<()> {
+    let manifest_path = Path::new("target/deploy/verifier-tasks.json");
+    if let Some(dir) = manifest_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(manifest_path, TASK_MANIFEST)?;
+
+    let manifest = TaskManifest::builtin()?;
+    for task in &manifest.tasks {
+        println!(
+            "{:#010x} {:>5} {} ({} v{})",
+            task.type_tag, task.size, task.path, task.name, task.version
+        );
+    }
+    println!(
+        "Saved {} tasks to {}",
+        manifest.tasks.len(),
+        manifest_path.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use clap::Parser;
+use client::{
+    simulator::{load_job, simulate},
+    TaskManifest,
+};
+use std::fs;
+use std::path::PathBuf;
+
+/// Run a verifier job on the host and trace every scheduler step
+#[derive(Parser, Debug)]
+#[clap(author, version, about = "Verifier job simulator")]
+struct Args {
+    /// Verifier account data holding the job, e.g. from `solana account --output-file`
+    job: PathBuf,
+
+    /// Save the trace as JSON instead of printing it
+    #[clap(long)]
+    output: Option<PathBuf>,
+
+    /// Task manifest of the verifier build that queued the job
+    #[clap(long)]
+    manifest: Option<PathBuf>,
+
+    /// Stop after this many steps
+    #[clap(long, default_value = "1000000")]
+    max_steps: usize,
+}
+
+fn main() -> client::Result<()> {
+    let args = Args::parse();
+
+    let manifest = match &args.manifest {
+        Some(path) => TaskManifest::load(path)?,
+        None => TaskManifest::builtin()?,
+    };
+    let mut stack = load_job(&fs::read(&args.job)?)?;
+    let trace = simulate(&mut stack, &manifest, args.max_steps);
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, serde_json::to_string_pretty(&trace.to_json())?)?;
+            println!(
+                "Saved trace of {} steps to {}",
+                trace.steps.len(),
+                path.display()
+            );
+        }
+        None => trace.print(),
+    }
+
+    Ok(())
+}
@@ -37,6 +37,14 @@ pub struct Config {
     /// Buffer chunk size for program deployment
     #[clap(long, default_value = "900")]
     pub buffer_chunk_size: usize,
+
+    /// Buffer capacity in bytes for verifier stack accounts
+    #[clap(long, default_value = "65536")]
+    pub stack_capacity: usize,
+
+    /// Id of the job account to create, derived from the current time when not given
+    #[clap(long)]
+    pub job_id: Option<u64>,
 }
 
 impl Config {
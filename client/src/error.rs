@@ -10,7 +10,7 @@ pub enum ClientError {
     SerdeError(#[from] serde_json::Error),
 
     #[error("Solana client error: {0}")]
-    SolanaClientError(#[from] solana_client::client_error::ClientError),
+    SolanaClientError(Box<solana_client::client_error::ClientError>),
 
     #[error("Borsh deserialization error: {0}")]
     BorshError(String),
@@ -34,4 +34,10 @@ pub enum ClientError {
     SerializationError(String),
 }
 
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(e: solana_client::client_error::ClientError) -> Self {
+        ClientError::SolanaClientError(Box::new(e))
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ClientError>;
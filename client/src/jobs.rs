@@ -0,0 +1,338 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    instruction::{AccountMeta, Instruction},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use verifier::{
+    events::JobCompleted,
+    instruction::VerifierInstruction,
+    state::{AccountHeader, VerificationResult},
+};
+
+use crate::{ClientError, Result};
+
+/// Create the job account `job_id` of the payer, with a stack of `capacity` bytes
+///
+/// The account lives at the address derived from the payer and the job id, so it can
+/// be found again from the id alone. Stacks larger than one instruction may allocate
+/// are grown with as many `GrowJob` instructions as needed. Returns the job account.
+pub fn create_job(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    job_id: u64,
+    capacity: usize,
+) -> Result<Pubkey> {
+    let (account, _) = AccountHeader::find_job_address(program_id, &payer.pubkey(), job_id);
+    let capacity = u32::try_from(capacity).map_err(|_| {
+        ClientError::TransactionError(format!("Stack capacity {} is too large", capacity))
+    })?;
+    let accounts = vec![
+        AccountMeta::new(account, false),
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let signature = send_authority_instruction(
+        client,
+        payer,
+        program_id,
+        &VerifierInstruction::CreateJob { job_id, capacity },
+        accounts.clone(),
+    )?;
+    println!("Job {} created at {}: {}", job_id, account, signature);
+
+    // Every instruction grows the account by at most MAX_PERMITTED_DATA_INCREASE
+    let space = AccountHeader::account_space(capacity as usize);
+    let created = space.min(MAX_PERMITTED_DATA_INCREASE);
+    let grow = Instruction::new_with_borsh(
+        *program_id,
+        &VerifierInstruction::GrowJob { capacity },
+        accounts,
+    );
+    let mut remaining = (space - created).div_ceil(MAX_PERMITTED_DATA_INCREASE);
+    while remaining > 0 {
+        let mut count = 1;
+        while count < remaining && fits(&payer.pubkey(), &vec![grow.clone(); count + 1]) {
+            count += 1;
+        }
+
+        let transaction = Transaction::new_signed_with_payer(
+            &vec![grow.clone(); count],
+            Some(&payer.pubkey()),
+            &[payer],
+            client.get_latest_blockhash()?,
+        );
+        let signature = client.send_and_confirm_transaction(&transaction)?;
+        println!("Job account grown {} times: {}", count, signature);
+        remaining -= count;
+    }
+
+    Ok(account)
+}
+
+/// Job id derived from the current time, for jobs that do not need a fixed one
+pub fn new_job_id() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// Push task frames to the back of a verifier account in as few transactions as fit
+///
+/// Frames are pushed in order, as with one `PushTask` per frame, so the last frame is
+/// executed first. The payer must be the authority of the job.
+pub fn push_tasks(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+    tasks: &[Vec<u8>],
+) -> Result<Vec<Signature>> {
+    push_batches(client, payer, program_id, account, tasks, |frames| {
+        VerifierInstruction::PushTaskBatch(frames)
+    })
+}
+
+/// Push data frames to the front of a verifier account in as few transactions as fit
+///
+/// Frames are pushed in order, as with one `PushData` per frame, so the last frame
+/// ends up on top. The payer must be the authority of the job.
+pub fn push_data(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+    frames: &[Vec<u8>],
+) -> Result<Vec<Signature>> {
+    push_batches(client, payer, program_id, account, frames, |frames| {
+        VerifierInstruction::PushDataBatch(frames)
+    })
+}
+
+fn push_batches(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+    frames: &[Vec<u8>],
+    batch: impl Fn(Vec<Vec<u8>>) -> VerifierInstruction,
+) -> Result<Vec<Signature>> {
+    let instruction = |frames: &[Vec<u8>]| {
+        Instruction::new_with_borsh(
+            *program_id,
+            &batch(frames.to_vec()),
+            vec![
+                AccountMeta::new(*account, false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+            ],
+        )
+    };
+
+    let mut signatures = Vec::new();
+    let mut start = 0;
+    while start < frames.len() {
+        // Grow the batch until the next frame would not fit in a packet
+        let mut end = start + 1;
+        if !fits(&payer.pubkey(), &[instruction(&frames[start..end])]) {
+            return Err(ClientError::TransactionError(format!(
+                "Frame of {} bytes does not fit in a transaction",
+                frames[start].len()
+            )));
+        }
+        while end < frames.len() && fits(&payer.pubkey(), &[instruction(&frames[start..=end])]) {
+            end += 1;
+        }
+
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction(&frames[start..end])],
+            Some(&payer.pubkey()),
+            &[payer],
+            client.get_latest_blockhash()?,
+        );
+        let signature = client.send_and_confirm_transaction(&transaction)?;
+        println!("Pushed {} frames: {}", end - start, signature);
+
+        signatures.push(signature);
+        start = end;
+    }
+
+    Ok(signatures)
+}
+
+/// Check that a transaction signed by the payer alone with these instructions fits in a packet
+fn fits(payer: &Pubkey, instructions: &[Instruction]) -> bool {
+    let transaction = Transaction::new_with_payer(instructions, Some(payer));
+    // One signature, prefixed by its compact length
+    let size = 1 + 64 + transaction.message.serialize().len();
+    size <= PACKET_DATA_SIZE
+}
+
+/// Find the result of a job in the logs of the transaction that finished it
+pub fn job_completed_from_logs(logs: &[String]) -> Option<JobCompleted> {
+    let event = STANDARD.encode(JobCompleted::EVENT);
+    logs.iter().find_map(|log| {
+        let mut fields = log.strip_prefix("Program data: ")?.split(' ');
+        if fields.next()? != event {
+            return None;
+        }
+        let data = STANDARD.decode(fields.next()?).ok()?;
+        JobCompleted::try_from_slice(&data).ok()
+    })
+}
+
+/// Fetch a confirmed transaction and read the result of the job it finished, if any
+pub fn job_completed(client: &RpcClient, signature: &Signature) -> Result<Option<JobCompleted>> {
+    let transaction = client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+            ..RpcTransactionConfig::default()
+        },
+    )?;
+    let logs: Option<Vec<String>> = transaction
+        .transaction
+        .meta
+        .and_then(|meta| meta.log_messages.into());
+
+    Ok(logs.and_then(|logs| job_completed_from_logs(&logs)))
+}
+
+/// Record the result of a finished job in its result account and close the job account
+///
+/// The payer must be the authority of the job, it receives the rent of the job account
+/// and pays for the result account. Returns the address of the result account.
+pub fn finalize(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+) -> Result<Pubkey> {
+    // The result address commits to the inputs recorded in the job header
+    let account_data = client.get_account_data(account)?;
+    let header: AccountHeader = account_data
+        .get(..AccountHeader::SIZE)
+        .ok_or_else(|| ClientError::SerializationError("Job account too small".to_string()))
+        .and_then(|header| {
+            utils::pod::from_bytes(header)
+                .map_err(|e| ClientError::SerializationError(e.to_string()))
+        })?;
+    let (result, _) = VerificationResult::find_address(program_id, account, &header.inputs_hash);
+    let signature = send_authority_instruction(
+        client,
+        payer,
+        program_id,
+        &VerifierInstruction::Finalize,
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(result, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )?;
+    println!("Job finalized: {}", signature);
+
+    Ok(result)
+}
+
+/// Clear both sides of a job so it can be pushed again, keeping its authority
+pub fn reset(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+) -> Result<Signature> {
+    let signature = send_authority_instruction(
+        client,
+        payer,
+        program_id,
+        &VerifierInstruction::Reset,
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ],
+    )?;
+    println!("Job reset: {}", signature);
+
+    Ok(signature)
+}
+
+/// Mark a pending job as failed with `reason`, so no more steps can run
+pub fn abort(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+    reason: u32,
+) -> Result<Signature> {
+    let signature = send_authority_instruction(
+        client,
+        payer,
+        program_id,
+        &VerifierInstruction::Abort { reason },
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ],
+    )?;
+    println!("Job aborted: {}", signature);
+
+    Ok(signature)
+}
+
+/// Close a verifier account, moving its lamports to `recipient`
+pub fn close(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+    recipient: &Pubkey,
+) -> Result<Signature> {
+    let signature = send_authority_instruction(
+        client,
+        payer,
+        program_id,
+        &VerifierInstruction::Close,
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+            AccountMeta::new(*recipient, false),
+        ],
+    )?;
+    println!("Account closed: {}", signature);
+
+    Ok(signature)
+}
+
+/// Send one instruction signed by the payer, the authority of the job
+fn send_authority_instruction(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    instruction: &VerifierInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Result<Signature> {
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_borsh(
+            *program_id,
+            instruction,
+            accounts,
+        )],
+        Some(&payer.pubkey()),
+        &[payer],
+        client.get_latest_blockhash()?,
+    );
+
+    Ok(client.send_and_confirm_transaction(&transaction)?)
+}
@@ -1,7 +1,15 @@
 pub mod config;
 pub mod error;
+pub mod jobs;
+pub mod simulator;
+pub mod tasks;
 pub mod utils;
 
 pub use config::Config;
 pub use error::{ClientError, Result};
+pub use jobs::{
+    abort, close, create_job, finalize, job_completed, job_completed_from_logs, new_job_id,
+    push_data, push_tasks, reset,
+};
+pub use tasks::{TaskEntry, TaskManifest};
 pub use utils::*;
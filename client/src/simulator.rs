@@ -0,0 +1,395 @@
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use utils::BidirectionalStack;
+use verifier::state::{AccountHeader, BidirectionalStackAccount, Side};
+
+use crate::{ClientError, Result, TaskManifest};
+
+/// Frames removed from and added to one side of the stack by a step
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameChanges {
+    /// Frames that were on top before the step, from the top downwards
+    pub popped: Vec<Vec<u8>>,
+    /// Frames that are on top after the step, from the top downwards
+    pub pushed: Vec<Vec<u8>>,
+}
+
+/// One scheduler step run by the simulator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// Task on top of the back side when the step started
+    pub task: String,
+    pub front: FrameChanges,
+    pub back: FrameChanges,
+    /// Stack indices after the step
+    pub front_index: usize,
+    pub back_index: usize,
+    pub elapsed: Duration,
+}
+
+/// How a simulated job stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// No tasks are left on the back side
+    Finished,
+    /// The step limit was reached with tasks still pending
+    StepLimit,
+    /// A step failed, which reverts the whole instruction on chain
+    Failed(String),
+}
+
+/// Steps a job went through on the host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+    pub outcome: Outcome,
+}
+
+/// Rebuild the stack of a verifier job from the account data holding it
+///
+/// The data may come from any source, e.g. `solana account --output-file`, and does not
+/// have to be aligned.
+pub fn load_job(data: &[u8]) -> Result<Box<BidirectionalStackAccount>> {
+    let invalid =
+        |reason: String| ClientError::SerializationError(format!("Invalid job: {}", reason));
+
+    let capacity = data
+        .len()
+        .checked_sub(AccountHeader::account_space(0))
+        .ok_or_else(|| invalid(format!("{} bytes is too short", data.len())))?;
+    let header: AccountHeader =
+        utils::pod::from_bytes(&data[..AccountHeader::SIZE]).map_err(|e| invalid(e.to_string()))?;
+    header.validate().map_err(|e| invalid(e.to_string()))?;
+
+    let (indices, buffer) =
+        data[AccountHeader::SIZE..].split_at(BidirectionalStackAccount::HEADER_SIZE);
+    let (front_index, back_index) = indices.split_at(size_of::<usize>());
+    let mut stack = BidirectionalStackAccount::with_capacity(capacity);
+    stack.front_index = usize::from_le_bytes(front_index.try_into().unwrap());
+    stack.back_index = usize::from_le_bytes(back_index.try_into().unwrap());
+    stack.buffer.copy_from_slice(buffer);
+
+    Ok(stack)
+}
+
+/// Run the tasks of a job until none are left, a step fails or `max_steps` have run
+///
+/// Steps go through [`BidirectionalStackAccount::execute`], the code the program runs.
+pub fn simulate(
+    stack: &mut BidirectionalStackAccount,
+    manifest: &TaskManifest,
+    max_steps: usize,
+) -> Trace {
+    let mut steps = Vec::new();
+    let mut front = SideFrames::new(Side::Front, stack);
+    let mut back = SideFrames::new(Side::Back, stack);
+
+    let outcome = loop {
+        if stack.is_empty_back() {
+            break Outcome::Finished;
+        }
+        if steps.len() == max_steps {
+            break Outcome::StepLimit;
+        }
+
+        let task = match stack.borrow_back() {
+            Ok(frame) => manifest.describe(frame),
+            Err(e) => break Outcome::Failed(e.to_string()),
+        };
+
+        let start = Instant::now();
+        let result = stack.execute();
+        let elapsed = start.elapsed();
+        if let Err(e) = result {
+            break Outcome::Failed(format!("{} failed: {}", task, e));
+        }
+
+        steps.push(TraceStep {
+            task,
+            front: front.update(stack),
+            back: back.update(stack),
+            front_index: stack.front_index,
+            back_index: stack.back_index,
+            elapsed,
+        });
+    };
+
+    Trace { steps, outcome }
+}
+
+/// Copies of the frames on one side of the stack, kept in step with it
+///
+/// Frames are stored from the bottom upwards, each with the distance of its data from
+/// the bottom of its side, so a step is compared against them from the top down only as
+/// far as it reached.
+struct SideFrames {
+    side: Side,
+    frames: Vec<(usize, Vec<u8>)>,
+}
+
+impl SideFrames {
+    fn new(side: Side, stack: &BidirectionalStackAccount) -> Self {
+        let mut frames = Self {
+            side,
+            frames: Vec::new(),
+        };
+        frames.update(stack);
+        frames
+    }
+
+    /// Compare the side with the frames it held before a step and take over its frames
+    ///
+    /// The walk down from the top stops at the first frame still in its place with the
+    /// same bytes, frames below it are left out. A task updated in place shows up as
+    /// popped and pushed again. A corrupted frame ends the walk like the bottom does.
+    fn update(&mut self, stack: &BidirectionalStackAccount) -> FrameChanges {
+        let side = self.side;
+        let buffer = stack.buffer.as_ptr() as usize;
+        let depth = |frame: &[u8]| {
+            let offset = frame.as_ptr() as usize - buffer;
+            match side {
+                Side::Front => offset,
+                Side::Back => stack.capacity() - offset,
+            }
+        };
+        let frames: Box<dyn Iterator<Item = _>> = match side {
+            Side::Front => Box::new(stack.front_frames()),
+            Side::Back => Box::new(stack.back_frames()),
+        };
+
+        let mut pushed = Vec::new();
+        let mut unchanged = 0;
+        for frame in frames.map_while(|frame| frame.ok()) {
+            let depth = depth(frame);
+            if let Ok(index) = self
+                .frames
+                .binary_search_by_key(&depth, |(depth, _)| *depth)
+            {
+                if self.frames[index].1 == frame {
+                    unchanged = index + 1;
+                    break;
+                }
+            }
+            pushed.push((depth, frame.to_vec()));
+        }
+
+        let popped = self
+            .frames
+            .drain(unchanged..)
+            .rev()
+            .map(|(_, frame)| frame)
+            .collect();
+        self.frames.extend(pushed.iter().rev().cloned());
+
+        FrameChanges {
+            popped,
+            pushed: pushed.into_iter().map(|(_, frame)| frame).collect(),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl FrameChanges {
+    fn to_json(&self) -> Value {
+        json!({
+            "popped": self.popped.iter().map(|frame| hex(frame)).collect::<Vec<_>>(),
+            "pushed": self.pushed.iter().map(|frame| hex(frame)).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl Trace {
+    /// Trace as JSON, with frames as hex strings and step times in microseconds
+    pub fn to_json(&self) -> Value {
+        let steps: Vec<Value> = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                json!({
+                    "step": index + 1,
+                    "task": step.task,
+                    "front": step.front.to_json(),
+                    "back": step.back.to_json(),
+                    "front_index": step.front_index,
+                    "back_index": step.back_index,
+                    "elapsed_us": step.elapsed.as_micros() as u64,
+                })
+            })
+            .collect();
+        let outcome = match &self.outcome {
+            Outcome::Finished => json!({ "status": "finished" }),
+            Outcome::StepLimit => json!({ "status": "step_limit" }),
+            Outcome::Failed(error) => json!({ "status": "failed", "error": error }),
+        };
+
+        json!({ "steps": steps, "outcome": outcome })
+    }
+
+    /// Print one line per step followed by the outcome
+    pub fn print(&self) {
+        for (index, step) in self.steps.iter().enumerate() {
+            println!(
+                "{:>6} {:<60} front -{} +{} back -{} +{} indices {}/{} {:?}",
+                index + 1,
+                step.task,
+                step.front.popped.len(),
+                step.front.pushed.len(),
+                step.back.popped.len(),
+                step.back.pushed.len(),
+                step.front_index,
+                step.back_index,
+                step.elapsed,
+            );
+        }
+        match &self.outcome {
+            Outcome::Finished => println!("Finished after {} steps", self.steps.len()),
+            Outcome::StepLimit => println!("Stopped after {} steps", self.steps.len()),
+            Outcome::Failed(error) => {
+                println!("Failed after {} steps: {}", self.steps.len(), error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arithmetic::{
+        add::Add,
+        mul::{Mul, MulInternal},
+    };
+    use solana_program::pubkey::Pubkey;
+    use utils::{Executable, Scheduler, StackValue};
+
+    const CAPACITY: usize = 1024;
+
+    /// Stack holding a data frame below the job and a `Mul` task
+    fn mul_job(x: u128, y: u128) -> Box<BidirectionalStackAccount> {
+        let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+        stack.push_front(&[9, 9]).unwrap();
+        stack.push_task(Mul::new(x, y)).unwrap();
+        stack
+    }
+
+    #[test]
+    fn test_simulate_reports_frame_changes() {
+        let mut stack = mul_job(5, 2);
+        let trace = simulate(&mut stack, &TaskManifest::builtin().unwrap(), usize::MAX);
+
+        assert_eq!(trace.outcome, Outcome::Finished);
+        assert_eq!(trace.steps.len(), 1 + 2 * 2);
+        assert_eq!(stack.peek_value::<u128>().unwrap(), 10);
+
+        // Mul replaces itself with its subtasks
+        let step = &trace.steps[0];
+        assert!(step.task.ends_with("Mul"), "{}", step.task);
+        assert_eq!(step.front, FrameChanges::default());
+        assert_eq!(
+            step.back,
+            FrameChanges {
+                popped: vec![Mul::new(5, 2).to_vec_with_type_tag()],
+                pushed: vec![
+                    Add::new(0, 5).to_vec_with_type_tag(),
+                    MulInternal::new(5, 2, 0, 0).to_vec_with_type_tag(),
+                ],
+            }
+        );
+
+        // Add leaves its result on top of the data frame pushed before the job
+        let step = &trace.steps[1];
+        assert_eq!(
+            step.front,
+            FrameChanges {
+                popped: vec![],
+                pushed: vec![5u128.to_frame()],
+            }
+        );
+        assert_eq!(
+            step.back.popped,
+            vec![Add::new(0, 5).to_vec_with_type_tag()]
+        );
+        assert!(step.back.pushed.is_empty());
+        assert_eq!(step.front_index, stack.front_index);
+
+        // MulInternal consumes the sum and is updated in place under the next Add
+        let step = &trace.steps[2];
+        assert_eq!(
+            step.front,
+            FrameChanges {
+                popped: vec![5u128.to_frame()],
+                pushed: vec![],
+            }
+        );
+        assert_eq!(
+            step.back,
+            FrameChanges {
+                popped: vec![MulInternal::new(5, 2, 0, 0).to_vec_with_type_tag()],
+                pushed: vec![
+                    Add::new(5, 5).to_vec_with_type_tag(),
+                    MulInternal::new(5, 2, 5, 1).to_vec_with_type_tag(),
+                ],
+            }
+        );
+
+        // The finished task is dropped as it was queued, and the sum it pops is pushed
+        // back unchanged, so the data side shows no change
+        let last = trace.steps.last().unwrap();
+        assert_eq!(last.back_index, CAPACITY);
+        assert_eq!(
+            last.back.popped,
+            vec![MulInternal::new(5, 2, 5, 1).to_vec_with_type_tag()]
+        );
+        assert_eq!(last.front, FrameChanges::default());
+    }
+
+    #[test]
+    fn test_simulate_stops_at_step_limit() {
+        let mut stack = mul_job(5, 2);
+        let trace = simulate(&mut stack, &TaskManifest::builtin().unwrap(), 2);
+
+        assert_eq!(trace.outcome, Outcome::StepLimit);
+        assert_eq!(trace.steps.len(), 2);
+        assert_eq!(stack.peek_value::<u128>().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_simulate_reports_failed_step() {
+        let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+        stack.push_back(&[0xFF; 8]).unwrap();
+        let trace = simulate(&mut stack, &TaskManifest::builtin().unwrap(), usize::MAX);
+
+        assert!(trace.steps.is_empty());
+        assert!(matches!(trace.outcome, Outcome::Failed(_)));
+    }
+
+    #[test]
+    fn test_load_job_from_unaligned_data() {
+        let mut account = vec![0; AccountHeader::account_space(CAPACITY)];
+        let (header, stack) = AccountHeader::split_mut(&mut account).unwrap();
+        header.initialize(Pubkey::new_unique()).unwrap();
+        stack.initialize();
+        stack.push_front(&[1, 2, 3]).unwrap();
+        stack.push_task(Add::new(1, 2)).unwrap();
+
+        // Shift the account by one byte so it cannot be cast in place
+        let mut data = vec![0; 1 + account.len()];
+        data[1..].copy_from_slice(&account);
+
+        let stack = load_job(&data[1..]).unwrap();
+        assert_eq!(stack.capacity(), CAPACITY);
+        assert_eq!(stack.borrow_front().unwrap(), &[1, 2, 3]);
+        assert_eq!(
+            stack.borrow_back().unwrap(),
+            Add::new(1, 2).to_vec_with_type_tag()
+        );
+
+        assert!(load_job(&data[1..AccountHeader::SIZE]).is_err());
+        data[1] ^= 0xFF;
+        assert!(load_job(&data[1..]).is_err());
+    }
+}
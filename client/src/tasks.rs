@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+use utils::BidirectionalStack;
+use verifier::{scheduler::TASK_MANIFEST, state::BidirectionalStackAccount};
+
+use crate::{ClientError, Result};
+
+/// A task type listed in the verifier task manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskEntry {
+    pub path: String,
+    pub name: String,
+    pub version: u32,
+    pub type_tag: u32,
+    pub size: usize,
+    /// Tags of older identifiers and versions the verifier still runs as this task
+    pub legacy_tags: Vec<u32>,
+}
+
+/// Task types the verifier can execute, keyed by the tag that starts their frames
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskManifest {
+    pub tasks: Vec<TaskEntry>,
+}
+
+impl TaskManifest {
+    /// Manifest of the verifier build this client was compiled against
+    pub fn builtin() -> Result<Self> {
+        Self::from_json(TASK_MANIFEST)
+    }
+
+    /// Read a manifest saved from a verifier build
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parse a manifest in the format written by the verifier build script
+    pub fn from_json(json: &str) -> Result<Self> {
+        let manifest: Value = serde_json::from_str(json)?;
+        let tasks = manifest["tasks"]
+            .as_array()
+            .ok_or_else(|| invalid("missing task list"))?
+            .iter()
+            .map(|task| {
+                Ok(TaskEntry {
+                    path: string(&task["path"], "task path")?,
+                    name: string(&task["name"], "task name")?,
+                    version: u32_value(&task["version"], "task version")?,
+                    type_tag: u32_value(&task["type_tag"], "type tag")?,
+                    size: task["size"]
+                        .as_u64()
+                        .and_then(|size| usize::try_from(size).ok())
+                        .ok_or_else(|| invalid("missing or invalid task size"))?,
+                    legacy_tags: task["legacy"]
+                        .as_array()
+                        .map(Vec::as_slice)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|legacy| u32_value(&legacy["type_tag"], "legacy type tag"))
+                        .collect::<Result<_>>()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { tasks })
+    }
+
+    /// Find the task type a frame tag belongs to, including tags of older versions
+    pub fn find(&self, type_tag: u32) -> Option<&TaskEntry> {
+        self.tasks
+            .iter()
+            .find(|task| task.type_tag == type_tag || task.legacy_tags.contains(&type_tag))
+    }
+
+    /// Name the task held in a task frame, or describe why it cannot be named
+    pub fn describe(&self, frame: &[u8]) -> String {
+        let Some(tag) = frame.first_chunk::<4>() else {
+            return format!("<truncated frame of {} bytes>", frame.len());
+        };
+        let type_tag = u32::from_be_bytes(*tag);
+        match self.find(type_tag) {
+            Some(task) if task.type_tag == type_tag => task.path.clone(),
+            Some(task) => format!("{} (legacy tag {:#010x})", task.path, type_tag),
+            None => format!("<unknown task {:#010x}>", type_tag),
+        }
+    }
+
+    /// Name the pending tasks in an account, from the next one to run downwards
+    pub fn pending_tasks(&self, stack: &BidirectionalStackAccount) -> Result<Vec<String>> {
+        stack
+            .back_frames()
+            .map(|frame| {
+                frame
+                    .map(|frame| self.describe(frame))
+                    .map_err(|e| ClientError::SerializationError(e.to_string()))
+            })
+            .collect()
+    }
+}
+
+fn string(value: &Value, what: &str) -> Result<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| invalid(&format!("missing {}", what)))
+}
+
+fn u32_value(value: &Value, what: &str) -> Result<u32> {
+    value
+        .as_u64()
+        .and_then(|value| u32::try_from(value).ok())
+        .ok_or_else(|| invalid(&format!("missing or invalid {}", what)))
+}
+
+fn invalid(reason: &str) -> ClientError {
+    ClientError::SerializationError(format!("Invalid task manifest: {}", reason))
+}
@@ -9,12 +9,14 @@ use solana_program::{
 use solana_sdk::{
     bpf_loader_upgradeable::UpgradeableLoaderState,
     commitment_config::CommitmentConfig,
-    signature::{Keypair, Signer},
+    hash::hash,
+    signature::{Keypair, Signature, Signer},
     transaction::Transaction,
 };
 use std::{fs, path::Path, thread::sleep};
+use verifier::instruction::VerifierInstruction;
 
-use crate::{ClientError, Config, Result};
+use crate::{simulator::load_job, ClientError, Config, Result};
 
 /// Initialize the Solana RPC client and verify connection
 pub fn initialize_client(config: &Config) -> Result<RpcClient> {
@@ -243,44 +245,7 @@ pub fn setup_account(
         println!("Using existing account: {}", account_keypair.pubkey());
         Ok(account_keypair)
     } else {
-        let account_keypair = Keypair::new();
-        println!("Creating account: {}", account_keypair.pubkey());
-
-        // Calculate the space needed for the account
-        let rent = client
-            .get_minimum_balance_for_rent_exemption(space)
-            .map_err(ClientError::SolanaClientError)?;
-
-        // Create a transaction to create the account
-        let create_account_ix = system_instruction::create_account(
-            &payer.pubkey(),
-            &account_keypair.pubkey(),
-            rent,
-            space as u64,
-            program_id,
-        );
-
-        let blockhash = client
-            .get_latest_blockhash()
-            .map_err(ClientError::SolanaClientError)?;
-
-        let create_tx = Transaction::new_signed_with_payer(
-            &[create_account_ix],
-            Some(&payer.pubkey()),
-            &[payer, &account_keypair],
-            blockhash,
-        );
-
-        // Send and confirm the transaction
-        let create_sig = client
-            .send_and_confirm_transaction(&create_tx)
-            .map_err(|e| {
-                ClientError::TransactionError(format!(
-                    "Failed to send and confirm account creation transaction: {}",
-                    e
-                ))
-            })?;
-        println!("Created account: {}", create_sig);
+        let account_keypair = create_program_account(client, payer, program_id, space)?;
 
         // Ensure keypairs directory exists
         if !config.keypairs_dir.exists() {
@@ -294,6 +259,56 @@ pub fn setup_account(
     }
 }
 
+/// Create a new rent-exempt account of `space` bytes owned by the program
+///
+/// Solana accounts can hold up to 10 MiB, so callers size the account for the job
+/// at hand instead of relying on a fixed layout.
+pub fn create_program_account(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &solana_sdk::pubkey::Pubkey,
+    space: usize,
+) -> Result<Keypair> {
+    let account_keypair = Keypair::new();
+    println!("Creating account: {}", account_keypair.pubkey());
+
+    // Calculate the rent needed for the account
+    let rent = client
+        .get_minimum_balance_for_rent_exemption(space)
+        .map_err(ClientError::from)?;
+
+    // Create a transaction to create the account
+    let create_account_ix = system_instruction::create_account(
+        &payer.pubkey(),
+        &account_keypair.pubkey(),
+        rent,
+        space as u64,
+        program_id,
+    );
+
+    let blockhash = client.get_latest_blockhash().map_err(ClientError::from)?;
+
+    let create_tx = Transaction::new_signed_with_payer(
+        &[create_account_ix],
+        Some(&payer.pubkey()),
+        &[payer, &account_keypair],
+        blockhash,
+    );
+
+    // Send and confirm the transaction
+    let create_sig = client
+        .send_and_confirm_transaction(&create_tx)
+        .map_err(|e| {
+            ClientError::TransactionError(format!(
+                "Failed to send and confirm account creation transaction: {}",
+                e
+            ))
+        })?;
+    println!("Created account: {}", create_sig);
+
+    Ok(account_keypair)
+}
+
 /// Send an instruction to a program
 pub fn send_instruction(
     client: &RpcClient,
@@ -306,9 +321,7 @@ pub fn send_instruction(
     let instruction = Instruction::new_with_bytes(*program_id, instruction_data, accounts);
 
     // Get latest blockhash
-    let blockhash = client
-        .get_latest_blockhash()
-        .map_err(ClientError::SolanaClientError)?;
+    let blockhash = client.get_latest_blockhash().map_err(ClientError::from)?;
 
     // Create a transaction with the instruction
     let transaction = Transaction::new_signed_with_payer(
@@ -350,9 +363,7 @@ pub fn interact_with_program_instructions(
     instructions: &[Instruction],
 ) -> Result<()> {
     // Get latest blockhash
-    let blockhash = client
-        .get_latest_blockhash()
-        .map_err(ClientError::SolanaClientError)?;
+    let blockhash = client.get_latest_blockhash().map_err(ClientError::from)?;
 
     // Create a transaction with the instructions
     let transaction = Transaction::new_signed_with_payer(
@@ -397,7 +408,7 @@ pub fn deploy_program(
         .get_minimum_balance_for_rent_exemption(
             buffer_data_len + UpgradeableLoaderState::size_of_buffer_metadata(),
         )
-        .map_err(ClientError::SolanaClientError)?;
+        .map_err(ClientError::from)?;
 
     // Create buffer account
     let create_buffer_ix = bpf_loader_upgradeable::create_buffer(
@@ -410,9 +421,7 @@ pub fn deploy_program(
     .map_err(|e| ClientError::DeploymentError(e.to_string()))?;
 
     // Get latest blockhash
-    let blockhash = client
-        .get_latest_blockhash()
-        .map_err(ClientError::SolanaClientError)?;
+    let blockhash = client.get_latest_blockhash().map_err(ClientError::from)?;
 
     // Create and send transaction
     let create_buffer_tx = Transaction::new_signed_with_payer(
@@ -438,7 +447,7 @@ pub fn deploy_program(
         .get_minimum_balance_for_rent_exemption(
             programdata_len + UpgradeableLoaderState::size_of_programdata_metadata(),
         )
-        .map_err(ClientError::SolanaClientError)?;
+        .map_err(ClientError::from)?;
 
     // Create deploy instruction
     let deploy_ix = bpf_loader_upgradeable::deploy_with_max_program_len(
@@ -452,9 +461,7 @@ pub fn deploy_program(
     .map_err(|e| ClientError::DeploymentError(e.to_string()))?;
 
     // Get latest blockhash
-    let blockhash = client
-        .get_latest_blockhash()
-        .map_err(ClientError::SolanaClientError)?;
+    let blockhash = client.get_latest_blockhash().map_err(ClientError::from)?;
 
     // Create and send transaction
     let deploy_tx = Transaction::new_signed_with_payer(
@@ -494,9 +501,7 @@ pub fn write_program_to_buffer(
         );
 
         // Get latest blockhash for each chunk to avoid expired blockhash issues
-        let blockhash = client
-            .get_latest_blockhash()
-            .map_err(ClientError::SolanaClientError)?;
+        let blockhash = client.get_latest_blockhash().map_err(ClientError::from)?;
 
         let write_tx = Transaction::new_signed_with_payer(
             &[write_ix],
@@ -559,6 +564,90 @@ pub fn write_program_to_buffer(
     Ok(())
 }
 
+/// Upload a data frame too large for one transaction into a verifier account
+///
+/// The frame is written in chunks of `config.buffer_chunk_size` bytes into the staging
+/// area above the front side of the stack, then committed with its hash. Each chunk is
+/// confirmed before the next is sent, and chunks already staged are skipped, so calling
+/// this again after an interruption resumes from the last confirmed chunk. The payer
+/// must be the authority of the job.
+pub fn upload_frame(
+    client: &RpcClient,
+    payer: &Keypair,
+    program_id: &solana_sdk::pubkey::Pubkey,
+    account: &solana_sdk::pubkey::Pubkey,
+    frame: &[u8],
+    config: &Config,
+) -> Result<()> {
+    let accounts = vec![
+        AccountMeta::new(*account, false),
+        AccountMeta::new_readonly(payer.pubkey(), true),
+    ];
+    let send = |instruction: &VerifierInstruction| -> Result<Signature> {
+        let transaction = Transaction::new_signed_with_payer(
+            &[Instruction::new_with_borsh(
+                *program_id,
+                instruction,
+                accounts.clone(),
+            )],
+            Some(&payer.pubkey()),
+            &[payer],
+            client.get_latest_blockhash().map_err(ClientError::from)?,
+        );
+        Ok(client.send_and_confirm_transaction(&transaction)?)
+    };
+
+    // Find the first chunk that does not hold the frame bytes yet
+    let account_data = client
+        .get_account_data(account)
+        .map_err(ClientError::from)?;
+    // RPC data is not aligned for the stack indices, so it is copied out
+    let stack = load_job(&account_data)?;
+    let staged = stack
+        .staged(frame.len())
+        .map_err(|e| ClientError::TransactionError(format!("Frame does not fit: {}", e)))?;
+    let resume = frame
+        .chunks(config.buffer_chunk_size)
+        .zip(staged.chunks(config.buffer_chunk_size))
+        .position(|(chunk, staged)| chunk != staged)
+        .unwrap_or(frame.len().div_ceil(config.buffer_chunk_size));
+    if resume > 0 {
+        println!(
+            "Resuming upload at offset {}",
+            resume * config.buffer_chunk_size
+        );
+    }
+
+    for (index, chunk) in frame
+        .chunks(config.buffer_chunk_size)
+        .enumerate()
+        .skip(resume)
+    {
+        let offset = index * config.buffer_chunk_size;
+        send(&VerifierInstruction::WriteChunk {
+            offset: u32::try_from(offset)
+                .map_err(|e| ClientError::SerializationError(e.to_string()))?,
+            bytes: chunk.to_vec(),
+        })
+        .map_err(|e| {
+            ClientError::TransactionError(format!(
+                "Failed to write chunk at offset {}: {}",
+                offset, e
+            ))
+        })?;
+    }
+
+    let signature = send(&VerifierInstruction::CommitFrame {
+        length: u32::try_from(frame.len())
+            .map_err(|e| ClientError::SerializationError(e.to_string()))?,
+        hash: Some(hash(frame).to_bytes()),
+    })
+    .map_err(|e| ClientError::TransactionError(format!("Failed to commit frame: {}", e)))?;
+    println!("Committed frame of {} bytes: {}", frame.len(), signature);
+
+    Ok(())
+}
+
 /// Verify that the buffer account contains the expected program data
 fn verify_buffer_data(
     client: &RpcClient,
@@ -568,7 +657,7 @@ fn verify_buffer_data(
     // Get the buffer account data
     let account_data = client
         .get_account_data(&buffer_keypair.pubkey())
-        .map_err(ClientError::SolanaClientError)?;
+        .map_err(ClientError::from)?;
 
     // The buffer account data starts with metadata (UpgradeableLoaderState::Buffer), followed by the program data
     // The size of the metadata is defined by size_of_buffer_metadata()
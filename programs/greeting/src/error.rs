@@ -21,6 +21,9 @@ pub enum GreetingError {
 
     #[error("Error serializing scheduler")]
     SchedulerSerializationError,
+
+    #[error("Invalid greeting account data")]
+    InvalidAccountData,
 }
 
 impl From<GreetingError> for ProgramError {
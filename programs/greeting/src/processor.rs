@@ -7,7 +7,7 @@ use solana_program::{
 };
 use utils::AccountCast;
 
-use crate::{instruction::GreetingInstruction, state::GreetingAccount};
+use crate::{error::GreetingError, instruction::GreetingInstruction, state::GreetingAccount};
 
 /// Program state handler
 pub struct Processor;
@@ -23,7 +23,10 @@ impl Processor {
 
         // Increment and store the number of times the account has been greeted
         let mut data = account.try_borrow_mut_data()?;
-        let greeting_account = GreetingAccount::cast_mut(*data);
+        let greeting_account = GreetingAccount::cast_mut(*data).map_err(|e| {
+            msg!("Error: {}", e);
+            GreetingError::InvalidAccountData
+        })?;
         greeting_account.counter += 1;
         greeting_account.double_counter += 2;
 
@@ -1,5 +1,6 @@
-use utils::AccountCast;
+use utils::{AccountCast, Pod};
 /// Define the type of state stored in accounts
+#[repr(C)]
 #[derive(Debug)]
 pub struct GreetingAccount {
     /// number of greetings
@@ -7,7 +8,11 @@ pub struct GreetingAccount {
 
     pub double_counter: u8,
 
+    pub _padding: [u8; 3],
+
     pub data: [u8; 1048576],
 }
 
+unsafe impl Pod for GreetingAccount {}
+
 impl AccountCast for GreetingAccount {}
@@ -1,47 +1,90 @@
 use std::fmt::Debug;
 
+use thiserror::Error;
+
+pub mod pod;
+mod registry;
+mod value;
+
+pub use pod::{CastError, Pod};
+pub use registry::{assert_unique_tags, Decoder, LegacyTag, TaskInfo, TaskVisitor};
+pub use value::{check_length, StackValue, ValueError, ARRAY_TAG};
+
 /// Trait for safely casting between account data and Rust types
-pub trait AccountCast: Sized {
+pub trait AccountCast: Pod {
     /// Cast a slice to an immutable reference of Self
-    fn cast(slice: &[u8]) -> &Self {
-        assert_eq!(slice.len(), std::mem::size_of::<Self>());
-        unsafe { &*(slice.as_ptr() as *const Self) }
+    fn cast(slice: &[u8]) -> Result<&Self, CastError> {
+        pod::cast(slice)
     }
 
     /// Cast a mutable slice to a mutable reference of Self
-    fn cast_mut(slice: &mut [u8]) -> &mut Self {
-        assert_eq!(slice.len(), std::mem::size_of::<Self>());
-        unsafe { &mut *(slice.as_mut_ptr() as *mut Self) }
+    fn cast_mut(slice: &mut [u8]) -> Result<&mut Self, CastError> {
+        pod::cast_mut(slice)
     }
 }
 
 pub trait BidirectionalStack {
-    type Error: std::error::Error + Debug;
+    type Error: std::error::Error + Debug + From<ValueError> + From<TaskError>;
 
     fn push_front(&mut self, data: &[u8]) -> Result<(), Self::Error>;
     fn push_back(&mut self, data: &[u8]) -> Result<(), Self::Error>;
-    fn pop_front(&mut self);
-    fn pop_back(&mut self);
-    fn borrow_front(&self) -> &[u8];
-    fn borrow_back(&self) -> &[u8];
-    fn borrow_mut_front(&mut self) -> &mut [u8];
-    fn borrow_mut_back(&mut self) -> &mut [u8];
+    fn pop_front(&mut self) -> Result<(), Self::Error>;
+    fn pop_back(&mut self) -> Result<(), Self::Error>;
+    fn borrow_front(&self) -> Result<&[u8], Self::Error>;
+    fn borrow_back(&self) -> Result<&[u8], Self::Error>;
+    fn borrow_mut_front(&mut self) -> Result<&mut [u8], Self::Error>;
+    fn borrow_mut_back(&mut self) -> Result<&mut [u8], Self::Error>;
     fn is_empty_front(&self) -> bool;
     fn is_empty_back(&self) -> bool;
+
+    /// Borrow the `n`-th front frame, counting from zero at the top, without popping
+    fn borrow_front_at(&self, n: usize) -> Result<&[u8], Self::Error>;
+    /// Borrow the `n`-th back frame, counting from zero at the top, without popping
+    fn borrow_back_at(&self, n: usize) -> Result<&[u8], Self::Error>;
+    /// Iterate over the front frames from the top of the stack downwards
+    fn front_frames(&self) -> impl Iterator<Item = Result<&[u8], Self::Error>> + '_;
+    /// Iterate over the back frames from the top of the stack downwards
+    fn back_frames(&self) -> impl Iterator<Item = Result<&[u8], Self::Error>> + '_;
+    /// Drop the top `n` front frames at once, or none if there are fewer than `n`
+    fn pop_front_n(&mut self, n: usize) -> Result<(), Self::Error>;
+
+    /// Push a tagged value as a new frame on the front (data) side
+    fn push_value<V: StackValue>(&mut self, value: &V) -> Result<(), Self::Error> {
+        self.push_front(&value.to_frame())
+    }
+
+    /// Read the value in the top front frame without removing it
+    fn peek_value<V: StackValue>(&self) -> Result<V, Self::Error> {
+        Ok(V::from_frame(self.borrow_front()?)?)
+    }
+
+    /// Read the value in the `n`-th front frame, counting from zero at the top
+    fn peek_value_at<V: StackValue>(&self, n: usize) -> Result<V, Self::Error> {
+        Ok(V::from_frame(self.borrow_front_at(n)?)?)
+    }
+
+    /// Remove the top front frame and return the value it holds
+    ///
+    /// The frame is left in place when it does not hold a `V`.
+    fn pop_value<V: StackValue>(&mut self) -> Result<V, Self::Error> {
+        let value = self.peek_value()?;
+        self.pop_front()?;
+        Ok(value)
+    }
 }
 
 pub trait Scheduler: BidirectionalStack {
-    fn push_task<T: Executable>(&mut self, task: T) {
-        self.push_back(&task.to_vec_with_type_tag()).unwrap();
+    fn push_task<T: Executable>(&mut self, task: T) -> Result<(), Self::Error> {
+        self.push_back(&task.to_vec_with_type_tag())
     }
-    fn push_data(&mut self, data: &[u8]) {
-        self.push_front(data).unwrap();
+    fn push_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.push_front(data)
     }
-    fn pop_task(&mut self) {
-        self.pop_back();
+    fn pop_task(&mut self) -> Result<(), Self::Error> {
+        self.pop_back()
     }
-    fn pop_data(&mut self) {
-        self.pop_front();
+    fn pop_data(&mut self) -> Result<(), Self::Error> {
+        self.pop_front()
     }
 }
 
@@ -49,77 +92,114 @@ pub trait Scheduler: BidirectionalStack {
 pub trait TypeIdentifiable {
     /// Returns a unique type ID based on the type name using a cryptographic hash
     const TYPE_ID: u32;
+    /// Identifier the type ID is derived from
+    const TYPE_NAME: &'static str;
+    /// Version of the type layout under its identifier
+    const TYPE_VERSION: u32;
+}
+
+/// FNV-1a hash of `bytes`, usable in const contexts
+///
+/// This is a non-cryptographic hash, so type IDs derived from it are checked for
+/// collisions when tasks are registered.
+pub const fn fnv1a(bytes: &[u8]) -> u32 {
+    // FNV-1a hash algorithm constants
+    const FNV_OFFSET_BASIS: u32 = 2166136261;
+
+    fnv1a_update(FNV_OFFSET_BASIS, bytes)
 }
 
-// For automatic implementation of TypeIdentifiable
+const fn fnv1a_update(mut hash: u32, bytes: &[u8]) -> u32 {
+    const FNV_PRIME: u32 = 16777619;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+/// Type ID of version `version` of the type identified by `name`
+///
+/// Version 0 hashes the bare name. Types without an explicit identifier use their
+/// module path as the name at version 0, so their IDs match the ones derived before
+/// identifiers could be versioned.
+pub const fn type_id(name: &str, version: u32) -> u32 {
+    let hash = fnv1a(name.as_bytes());
+    if version == 0 {
+        return hash;
+    }
+    fnv1a_update(fnv1a_update(hash, b"@"), &version.to_be_bytes())
+}
+
+/// Implement [`TypeIdentifiable`] for a type
+///
+/// With only a type, the ID is derived from the module path and the type name, so
+/// moving or renaming the type changes it. Passing a stable identifier and a version,
+/// e.g. `impl_type_identifiable!(HadesPermutation, "stark.hades", 1)`, keeps the ID
+/// fixed wherever the type lives. Bump the version whenever the layout changes and
+/// register a decoder for the old one with [`register_tasks!`].
 #[macro_export]
 macro_rules! impl_type_identifiable {
     ($type:ty) => {
         impl TypeIdentifiable for $type {
-            // Generate a compile-time constant value based on type name using FNV-1a hash
-            // This is a non-cryptographic but better hash function that can be used in const contexts
-            const TYPE_ID: u32 = {
-                // Create a hash from the fully qualified type name at compile time
-                let full_type_name = concat!(module_path!(), "::", stringify!($type));
-                let bytes = full_type_name.as_bytes();
-                let len = bytes.len();
-
-                // FNV-1a hash algorithm constants
-                const FNV_PRIME: u32 = 16777619;
-                const FNV_OFFSET_BASIS: u32 = 2166136261;
-
-                // FNV-1a hash computation
-                let mut hash = FNV_OFFSET_BASIS;
-                let mut i = 0;
-                while i < len {
-                    hash ^= bytes[i] as u32;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                    i += 1;
-                }
-
-                hash
-            };
+            const TYPE_ID: u32 = $crate::type_id(Self::TYPE_NAME, Self::TYPE_VERSION);
+            const TYPE_NAME: &'static str = concat!(module_path!(), "::", stringify!($type));
+            const TYPE_VERSION: u32 = 0;
+        }
+    };
+    ($type:ty, $name:literal, $version:literal) => {
+        impl TypeIdentifiable for $type {
+            const TYPE_ID: u32 = $crate::type_id(Self::TYPE_NAME, Self::TYPE_VERSION);
+            const TYPE_NAME: &'static str = $name;
+            const TYPE_VERSION: u32 = $version;
         }
     };
 }
 
-pub trait Executable: Sized + TypeIdentifiable {
+/// Errors raised by a task that cannot run with the state it was given
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TaskError {
+    #[error("Invalid task state: {0}")]
+    InvalidState(&'static str),
+}
+
+pub trait Executable: Pod + TypeIdentifiable {
     /// The type tag is now automatically derived from TypeIdentifiable trait
     /// Using u32 instead of u8 for a much larger ID space
     const TYPE_TAG: u32 = Self::TYPE_ID;
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>>;
+
+    /// Run one step of the task and return the tasks to schedule next
+    ///
+    /// Failures surface as the stack error, so missing or mistyped data frames keep
+    /// the stack's own error and problems with the task itself arrive as [`TaskError`].
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error>;
     fn is_finished(&mut self) -> bool {
         false
     }
 
     /// Cast a slice to an immutable reference of Self
-    fn cast(slice: &[u8]) -> &Self {
-        assert_eq!(slice.len(), std::mem::size_of::<Self>());
-        unsafe { &*(slice.as_ptr() as *const Self) }
+    fn cast(slice: &[u8]) -> Result<&Self, CastError> {
+        pod::cast(slice)
     }
 
     /// Cast a mutable slice to a mutable reference of Self
-    fn cast_mut(slice: &mut [u8]) -> &mut Self {
-        assert_eq!(slice.len(), std::mem::size_of::<Self>());
-        unsafe { &mut *(slice.as_mut_ptr() as *mut Self) }
+    fn cast_mut(slice: &mut [u8]) -> Result<&mut Self, CastError> {
+        pod::cast_mut(slice)
     }
 
-    fn as_bytes(&self) -> &[u8] {
-        unsafe {
-            std::slice::from_raw_parts(
-                (self as *const Self) as *const u8,
-                std::mem::size_of::<Self>(),
-            )
-        }
+    /// Copy a task out of a slice with any alignment, such as a frame on the stack
+    fn from_bytes(slice: &[u8]) -> Result<Self, CastError> {
+        pod::from_bytes(slice)
     }
 
-    fn as_bytes_mut(&mut self) -> &mut [u8] {
-        unsafe {
-            std::slice::from_raw_parts_mut(
-                (self as *mut Self) as *mut u8,
-                std::mem::size_of::<Self>(),
-            )
-        }
+    fn as_bytes(&self) -> &[u8] {
+        pod::bytes_of(self)
     }
 
     fn to_vec_with_type_tag(&self) -> Vec<u8> {
@@ -0,0 +1,223 @@
+use std::any::type_name;
+use std::mem::{align_of, size_of};
+use std::{ptr, slice};
+
+use thiserror::Error;
+
+/// Marker for plain-old-data types that can be read directly from account bytes
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` or `#[repr(transparent)]`, must not contain padding,
+/// pointers or references, and every byte pattern accepted by
+/// [`is_valid_bit_pattern`](Pod::is_valid_bit_pattern) must be a valid value of the type.
+/// Types with fields that do not accept every bit pattern, such as enums or `bool`,
+/// have to override the check.
+pub unsafe trait Pod: Sized {
+    /// Check that `bytes` hold a valid value before they are reinterpreted as `Self`
+    ///
+    /// Called with exactly `size_of::<Self>()` bytes.
+    fn is_valid_bit_pattern(_bytes: &[u8]) -> bool {
+        true
+    }
+}
+
+/// Errors raised when bytes cannot be viewed as a [`Pod`] type
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CastError {
+    #[error("Invalid length for {type_name} - expected {expected} bytes, found {found}")]
+    InvalidLength {
+        type_name: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Misaligned data for {type_name} - required alignment is {align}")]
+    Misaligned {
+        type_name: &'static str,
+        align: usize,
+    },
+
+    #[error("Invalid bit pattern for {type_name}")]
+    InvalidBitPattern { type_name: &'static str },
+}
+
+/// Check the length and the bit pattern of bytes about to be read as `T`
+fn check<T: Pod>(bytes: &[u8]) -> Result<(), CastError> {
+    if bytes.len() != size_of::<T>() {
+        return Err(CastError::InvalidLength {
+            type_name: type_name::<T>(),
+            expected: size_of::<T>(),
+            found: bytes.len(),
+        });
+    }
+    if !T::is_valid_bit_pattern(bytes) {
+        return Err(CastError::InvalidBitPattern {
+            type_name: type_name::<T>(),
+        });
+    }
+    Ok(())
+}
+
+/// Check that bytes start at an address suitable for `T`
+fn check_alignment<T>(bytes: &[u8]) -> Result<(), CastError> {
+    if !(bytes.as_ptr() as usize).is_multiple_of(align_of::<T>()) {
+        return Err(CastError::Misaligned {
+            type_name: type_name::<T>(),
+            align: align_of::<T>(),
+        });
+    }
+    Ok(())
+}
+
+/// View bytes as a reference to `T`
+pub fn cast<T: Pod>(bytes: &[u8]) -> Result<&T, CastError> {
+    check_alignment::<T>(bytes)?;
+    check::<T>(bytes)?;
+    Ok(unsafe { &*(bytes.as_ptr() as *const T) })
+}
+
+/// View bytes as a mutable reference to `T`
+pub fn cast_mut<T: Pod>(bytes: &mut [u8]) -> Result<&mut T, CastError> {
+    check_alignment::<T>(bytes)?;
+    check::<T>(bytes)?;
+    Ok(unsafe { &mut *(bytes.as_mut_ptr() as *mut T) })
+}
+
+/// Copy a `T` out of bytes at any alignment
+pub fn from_bytes<T: Pod>(bytes: &[u8]) -> Result<T, CastError> {
+    check::<T>(bytes)?;
+    Ok(unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+/// Check the discriminant of a fieldless `#[repr(u64)]` enum stored at `offset`
+///
+/// Meant for [`Pod::is_valid_bit_pattern`] of types holding such an enum, whose variants
+/// are numbered from zero up to `last`. A full word leaves no padding around the enum,
+/// whatever fields surround it.
+pub fn is_valid_discriminant(bytes: &[u8], offset: usize, last: u64) -> bool {
+    bytes
+        .get(offset..offset + size_of::<u64>())
+        .and_then(|discriminant| discriminant.try_into().ok())
+        .is_some_and(|discriminant| u64::from_ne_bytes(discriminant) <= last)
+}
+
+/// View a value as its bytes
+pub fn bytes_of<T: Pod>(value: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts((value as *const T) as *const u8, size_of::<T>()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(u8)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Flag {
+        Off = 0,
+        On = 1,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Tagged {
+        value: u32,
+        flag: Flag,
+        padding: [u8; 3],
+    }
+
+    unsafe impl Pod for Tagged {
+        fn is_valid_bit_pattern(bytes: &[u8]) -> bool {
+            bytes[4] <= Flag::On as u8
+        }
+    }
+
+    /// Eight-byte aligned storage so casts can be tested at chosen offsets
+    #[repr(C, align(8))]
+    struct Aligned([u8; 16]);
+
+    #[test]
+    fn test_round_trip() {
+        let value = Tagged {
+            value: 0xDEADBEEF,
+            flag: Flag::On,
+            padding: [0; 3],
+        };
+        let mut storage = Aligned([0; 16]);
+        storage.0[..8].copy_from_slice(bytes_of(&value));
+
+        assert_eq!(cast::<Tagged>(&storage.0[..8]), Ok(&value));
+        assert_eq!(from_bytes::<Tagged>(&storage.0[..8]), Ok(value));
+
+        cast_mut::<Tagged>(&mut storage.0[..8]).unwrap().flag = Flag::Off;
+        assert_eq!(storage.0[4], Flag::Off as u8);
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let storage = Aligned([0; 16]);
+        assert!(matches!(
+            cast::<Tagged>(&storage.0[..7]),
+            Err(CastError::InvalidLength {
+                expected: 8,
+                found: 7,
+                ..
+            })
+        ));
+        assert!(matches!(
+            from_bytes::<Tagged>(&storage.0[..9]),
+            Err(CastError::InvalidLength {
+                expected: 8,
+                found: 9,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_misaligned() {
+        let mut storage = Aligned([0; 16]);
+        assert!(matches!(
+            cast::<Tagged>(&storage.0[1..9]),
+            Err(CastError::Misaligned { align: 4, .. })
+        ));
+        assert!(matches!(
+            cast_mut::<Tagged>(&mut storage.0[2..10]),
+            Err(CastError::Misaligned { align: 4, .. })
+        ));
+
+        // Copying out does not care about alignment
+        assert!(from_bytes::<Tagged>(&storage.0[1..9]).is_ok());
+    }
+
+    #[test]
+    fn test_is_valid_discriminant() {
+        let mut bytes = [0; 12];
+        bytes[4..].copy_from_slice(&2u64.to_ne_bytes());
+
+        assert!(is_valid_discriminant(&bytes, 4, 2));
+        assert!(!is_valid_discriminant(&bytes, 4, 1));
+        // The discriminant must fit in the bytes
+        assert!(!is_valid_discriminant(&bytes, 5, 2));
+    }
+
+    #[test]
+    fn test_invalid_bit_pattern() {
+        let mut storage = Aligned([0; 16]);
+        storage.0[4] = 2;
+        storage.0[9] = 7;
+
+        assert!(matches!(
+            cast::<Tagged>(&storage.0[..8]),
+            Err(CastError::InvalidBitPattern { .. })
+        ));
+        assert!(matches!(
+            cast_mut::<Tagged>(&mut storage.0[..8]),
+            Err(CastError::InvalidBitPattern { .. })
+        ));
+        assert!(matches!(
+            from_bytes::<Tagged>(&storage.0[5..13]),
+            Err(CastError::InvalidBitPattern { .. })
+        ));
+    }
+}
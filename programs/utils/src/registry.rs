@@ -0,0 +1,225 @@
+use crate::{CastError, Executable};
+
+/// Entry describing one registered task type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskInfo {
+    /// Path of the task type, starting with the crate that registered it
+    pub path: &'static str,
+    /// Identifier the type tag is derived from
+    pub name: &'static str,
+    /// Version of the task layout under its identifier
+    pub version: u32,
+    /// Tag written in front of the task in its stack frame
+    pub type_tag: u32,
+    /// Size of the task state following the tag
+    pub size: usize,
+    /// Older identifiers and versions still accepted for this task
+    pub legacy: &'static [LegacyTag],
+}
+
+/// Identifier and version a task was known under before, with the tag derived from them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegacyTag {
+    pub name: &'static str,
+    pub version: u32,
+    pub type_tag: u32,
+}
+
+/// Read a task from the bytes following the tag in its frame
+pub type Decoder<T> = fn(&[u8]) -> Result<T, CastError>;
+
+/// Code run for a task type that is only known from its tag at runtime
+///
+/// The dispatch functions generated by [`register_tasks!`](crate::register_tasks) call
+/// [`visit`](TaskVisitor::visit) with the task type matching the tag, and with the
+/// decoder for the layout that tag stands for.
+pub trait TaskVisitor {
+    type Output;
+
+    fn visit<T: Executable>(self, decode: Decoder<T>) -> Self::Output;
+}
+
+/// The `n`-th tag across the current and legacy tags of every registered task
+const fn nth_tag(registries: &[&[TaskInfo]], mut n: usize) -> Option<u32> {
+    let mut i = 0;
+    while i < registries.len() {
+        let mut j = 0;
+        while j < registries[i].len() {
+            let task = &registries[i][j];
+            if n == 0 {
+                return Some(task.type_tag);
+            }
+            if n <= task.legacy.len() {
+                return Some(task.legacy[n - 1].type_tag);
+            }
+            n -= 1 + task.legacy.len();
+            j += 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Stop compilation when two registered tasks share a type tag
+///
+/// Legacy tags count as well, as a frame carrying one must still lead to a single task.
+/// Meant to be evaluated in a constant, for example over the task lists of every crate
+/// a dispatcher combines, so the collision shows up as a build error.
+pub const fn assert_unique_tags(registries: &[&[TaskInfo]]) {
+    let mut i = 0;
+    while let Some(type_tag) = nth_tag(registries, i) {
+        // Compare with every tag after this one
+        let mut j = i + 1;
+        while let Some(other) = nth_tag(registries, j) {
+            if other == type_tag {
+                panic!("two registered tasks share a TYPE_TAG");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Register the task types a crate provides
+///
+/// Expands to a `TASKS` list describing every task and a `dispatch` function that
+/// hands the task type matching a tag to a [`TaskVisitor`], or returns the visitor
+/// back when none matches. Entries may carry `#[cfg]` attributes. Invoke it once,
+/// at the crate root.
+///
+/// A task whose identifier or layout changed lists the identifiers and versions it
+/// was known under, each with a [`Decoder`] reading that old layout. Frames queued
+/// before a program upgrade keep running that way.
+///
+/// ```ignore
+/// utils::register_tasks! {
+///     add::Add,
+///     hades::HadesPermutation {
+///         ("stark::poseidon::hades::HadesPermutation", 0) => utils::pod::from_bytes,
+///     },
+/// }
+/// ```
+#[macro_export]
+macro_rules! register_tasks {
+    (
+        $(
+            $(#[$meta:meta])*
+            $task:path $({
+                $(($name:expr, $version:expr) => $decode:expr),* $(,)?
+            })?
+        ),* $(,)?
+    ) => {
+        /// Task types registered by this crate
+        pub const TASKS: &[$crate::TaskInfo] = &[
+            $(
+                $(#[$meta])*
+                $crate::TaskInfo {
+                    path: concat!(module_path!(), "::", stringify!($task)),
+                    name: <$task as $crate::TypeIdentifiable>::TYPE_NAME,
+                    version: <$task as $crate::TypeIdentifiable>::TYPE_VERSION,
+                    type_tag: <$task as $crate::Executable>::TYPE_TAG,
+                    size: ::std::mem::size_of::<$task>(),
+                    legacy: &[$($(
+                        $crate::LegacyTag {
+                            name: $name,
+                            version: $version,
+                            type_tag: $crate::type_id($name, $version),
+                        },
+                    )*)?],
+                },
+            )*
+        ];
+
+        const _: () = $crate::assert_unique_tags(&[TASKS]);
+
+        /// Call `visitor` with the registered task type whose tag is `type_tag`
+        pub fn dispatch<V: $crate::TaskVisitor>(type_tag: u32, visitor: V) -> Result<V::Output, V> {
+            $(
+                $(#[$meta])*
+                {
+                    if type_tag == <$task as $crate::Executable>::TYPE_TAG {
+                        return Ok(visitor.visit::<$task>($crate::pod::from_bytes));
+                    }
+                    $($(
+                        if type_tag == $crate::type_id($name, $version) {
+                            return Ok(visitor.visit::<$task>($decode));
+                        }
+                    )*)?
+                }
+            )*
+            Err(visitor)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn info(type_tag: u32) -> TaskInfo {
+        TaskInfo {
+            path: "task",
+            name: "task",
+            version: 1,
+            type_tag,
+            size: 0,
+            legacy: &[],
+        }
+    }
+
+    const fn with_legacy(type_tag: u32, legacy: &'static [LegacyTag]) -> TaskInfo {
+        TaskInfo {
+            legacy,
+            ..info(type_tag)
+        }
+    }
+
+    const fn legacy(type_tag: u32) -> LegacyTag {
+        LegacyTag {
+            name: "task",
+            version: 0,
+            type_tag,
+        }
+    }
+
+    #[test]
+    fn test_unique_tags() {
+        assert_unique_tags(&[]);
+        assert_unique_tags(&[&[info(1), info(2)], &[], &[info(3)]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "share a TYPE_TAG")]
+    fn test_duplicate_tag_in_one_list() {
+        assert_unique_tags(&[&[info(1), info(2), info(1)]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "share a TYPE_TAG")]
+    fn test_duplicate_tag_across_lists() {
+        assert_unique_tags(&[&[info(1), info(2)], &[info(3), info(2)]]);
+    }
+
+    #[test]
+    fn test_unique_legacy_tags() {
+        assert_unique_tags(&[
+            &[with_legacy(1, const { &[legacy(2), legacy(3)] }), info(4)],
+            &[with_legacy(5, const { &[legacy(6)] })],
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "share a TYPE_TAG")]
+    fn test_legacy_tag_shared_with_current_tag() {
+        assert_unique_tags(&[&[info(1)], &[with_legacy(2, const { &[legacy(1)] })]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "share a TYPE_TAG")]
+    fn test_legacy_tag_shared_with_legacy_tag() {
+        assert_unique_tags(&[&[
+            with_legacy(1, const { &[legacy(3)] }),
+            with_legacy(2, const { &[legacy(3)] }),
+        ]]);
+    }
+}
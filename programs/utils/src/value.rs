@@ -0,0 +1,211 @@
+use thiserror::Error;
+
+/// Tag bit marking a frame that holds an array of values
+///
+/// The remaining bits carry the tag of the array element, so element tags have to stay
+/// below this bit. Nested arrays share the tag of their innermost element.
+pub const ARRAY_TAG: u8 = 0x80;
+
+/// Errors raised when a data frame does not hold the value a task expects
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ValueError {
+    #[error("Missing value tag - the data frame is empty")]
+    MissingTag,
+
+    #[error("Value tag mismatch - expected {expected:#04x}, found {found:#04x}")]
+    TagMismatch { expected: u8, found: u8 },
+
+    #[error("Invalid value length - expected {expected} bytes, found {found}")]
+    InvalidLength { expected: usize, found: usize },
+
+    #[error("Invalid value encoding: {0}")]
+    InvalidEncoding(&'static str),
+}
+
+/// A value that can be stored in a single tagged data frame
+///
+/// The frame holds `VALUE_TAG` followed by the encoded value, so reading a frame as the
+/// wrong kind of value fails with [`ValueError::TagMismatch`] instead of being misread.
+pub trait StackValue: Sized {
+    /// One-byte tag identifying the kind of value in the frame
+    const VALUE_TAG: u8;
+
+    /// Size of the encoded value, or `None` when it depends on the value
+    const ENCODED_SIZE: Option<usize>;
+
+    /// Append the encoded value, without the tag, to `bytes`
+    fn encode(&self, bytes: &mut Vec<u8>);
+
+    /// Decode a value from its encoding, without the tag
+    fn decode(bytes: &[u8]) -> Result<Self, ValueError>;
+
+    /// Encode the value together with its tag as the contents of a data frame
+    fn to_frame(&self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(1 + Self::ENCODED_SIZE.unwrap_or_default());
+        frame.push(Self::VALUE_TAG);
+        self.encode(&mut frame);
+        frame
+    }
+
+    /// Decode a value from the contents of a data frame, checking its tag
+    fn from_frame(frame: &[u8]) -> Result<Self, ValueError> {
+        let (&tag, bytes) = frame.split_first().ok_or(ValueError::MissingTag)?;
+        if tag != Self::VALUE_TAG {
+            return Err(ValueError::TagMismatch {
+                expected: Self::VALUE_TAG,
+                found: tag,
+            });
+        }
+        Self::decode(bytes)
+    }
+}
+
+/// Check that an encoding has exactly the size a fixed-size value needs
+pub fn check_length(bytes: &[u8], expected: usize) -> Result<(), ValueError> {
+    if bytes.len() != expected {
+        return Err(ValueError::InvalidLength {
+            expected,
+            found: bytes.len(),
+        });
+    }
+    Ok(())
+}
+
+impl StackValue for u128 {
+    const VALUE_TAG: u8 = 0x01;
+    const ENCODED_SIZE: Option<usize> = Some(16);
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, ValueError> {
+        check_length(bytes, 16)?;
+        Ok(u128::from_be_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+/// Byte blobs of any length
+impl StackValue for Vec<u8> {
+    const VALUE_TAG: u8 = 0x02;
+    const ENCODED_SIZE: Option<usize> = None;
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, ValueError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Fixed-length arrays of fixed-size values, stored back to back in one frame
+impl<T: StackValue, const N: usize> StackValue for [T; N] {
+    const VALUE_TAG: u8 = ARRAY_TAG | T::VALUE_TAG;
+    const ENCODED_SIZE: Option<usize> = match T::ENCODED_SIZE {
+        Some(size) => Some(size * N),
+        None => None,
+    };
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.iter().for_each(|value| value.encode(bytes));
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, ValueError> {
+        let size = T::ENCODED_SIZE.ok_or(ValueError::InvalidEncoding(
+            "array elements must have a fixed size",
+        ))?;
+        check_length(bytes, size * N)?;
+
+        let values = bytes
+            .chunks_exact(size.max(1))
+            .take(N)
+            .map(T::decode)
+            .collect::<Result<Vec<T>, ValueError>>()?;
+        values
+            .try_into()
+            .map_err(|_| ValueError::InvalidEncoding("wrong number of array elements"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u128_round_trip() {
+        for value in [0u128, 1, u64::MAX as u128 + 1, u128::MAX] {
+            let frame = value.to_frame();
+            assert_eq!(frame.len(), 17);
+            assert_eq!(frame[0], u128::VALUE_TAG);
+            assert_eq!(u128::from_frame(&frame), Ok(value));
+        }
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for value in [vec![], vec![0u8], vec![0xAB; 1000]] {
+            let frame = value.to_frame();
+            assert_eq!(frame.len(), value.len() + 1);
+            assert_eq!(Vec::<u8>::from_frame(&frame), Ok(value));
+        }
+    }
+
+    #[test]
+    fn test_array_round_trip() {
+        let value = [1u128, 2, 3];
+        let frame = value.to_frame();
+        assert_eq!(frame[0], ARRAY_TAG | u128::VALUE_TAG);
+        assert_eq!(frame.len(), 1 + 3 * 16);
+        assert_eq!(<[u128; 3]>::from_frame(&frame), Ok(value));
+
+        let empty: [u128; 0] = [];
+        assert_eq!(<[u128; 0]>::from_frame(&empty.to_frame()), Ok(empty));
+    }
+
+    #[test]
+    fn test_tag_mismatch() {
+        assert_eq!(
+            Vec::<u8>::from_frame(&7u128.to_frame()),
+            Err(ValueError::TagMismatch {
+                expected: Vec::<u8>::VALUE_TAG,
+                found: u128::VALUE_TAG,
+            })
+        );
+        assert_eq!(
+            u128::from_frame(&[7u128, 8].to_frame()),
+            Err(ValueError::TagMismatch {
+                expected: u128::VALUE_TAG,
+                found: ARRAY_TAG | u128::VALUE_TAG,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        assert_eq!(u128::from_frame(&[]), Err(ValueError::MissingTag));
+        assert_eq!(
+            u128::from_frame(&[u128::VALUE_TAG, 1, 2, 3]),
+            Err(ValueError::InvalidLength {
+                expected: 16,
+                found: 3,
+            })
+        );
+        assert_eq!(
+            <[u128; 2]>::from_frame(&[1u128, 2, 3].to_frame()),
+            Err(ValueError::InvalidLength {
+                expected: 32,
+                found: 48,
+            })
+        );
+    }
+
+    #[test]
+    fn test_array_of_unsized_values() {
+        let frame = [vec![1u8], vec![2u8]].to_frame();
+        assert!(matches!(
+            <[Vec<u8>; 2]>::from_frame(&frame),
+            Err(ValueError::InvalidEncoding(_))
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 use std::io;
 use std::num::TryFromIntError;
 
+use solana_program::{msg, program_error::ProgramError};
 use thiserror::Error;
+use utils::{CastError, TaskError, ValueError};
 
 /// Custom errors for the verifier program
 #[derive(Error, Debug)]
@@ -9,6 +11,18 @@ pub enum VerifierError {
     #[error("Account not owned by program")]
     InvalidOwner,
 
+    /// The verifier account was passed as read-only.
+    #[error("Verifier account is not writable")]
+    AccountNotWritable,
+
+    /// The authority account does not match the job authority in the header.
+    #[error("Authority does not match the job authority")]
+    InvalidAuthority,
+
+    /// The job authority did not sign the instruction.
+    #[error("Missing job authority signature")]
+    MissingAuthoritySignature,
+
     #[error("Error deserializing scheduler")]
     SchedulerDeserializationError,
 
@@ -27,6 +41,34 @@ pub enum VerifierError {
     #[error(transparent)]
     TryFromInt(#[from] TryFromIntError),
 
+    /// The account data cannot hold the account header and an empty stack.
+    #[error("Account too small for the verifier account header")]
+    AccountTooSmall,
+
+    /// The account data does not start with the verifier magic value.
+    #[error("Invalid account magic - not a verifier account")]
+    InvalidMagic,
+
+    /// The account follows a layout version this program does not understand.
+    #[error("Unsupported account layout version {0}")]
+    UnsupportedVersion(u32),
+
+    /// Initialize was called on an account that already holds a job.
+    #[error("Account already initialized")]
+    AlreadyInitialized,
+
+    /// The account has not been initialized yet.
+    #[error("Account not initialized")]
+    NotInitialized,
+
+    /// The account header holds an unknown job status.
+    #[error("Invalid job status {0}")]
+    InvalidStatus(u8),
+
+    /// The job has finished and no longer accepts tasks, data or execution steps.
+    #[error("Job already finished")]
+    JobFinished,
+
     /// The stack is empty and cannot be popped from.
     #[error("Empty stack - attempted to read from an empty stack")]
     EmptyStack,
@@ -35,6 +77,14 @@ pub enum VerifierError {
     #[error("Stack capacity exceeded")]
     StackCapacity,
 
+    /// The stack indices or a frame length point outside the stack region.
+    #[error("Invalid frame - stack indices or frame length out of bounds")]
+    InvalidFrame,
+
+    /// A data frame does not hold the kind of value a task expects.
+    #[error(transparent)]
+    Value(#[from] ValueError),
+
     /// The task data length is invalid.
     #[error("Invalid task length - task data exceeds maximum allowed size")]
     InvalidTaskLength,
@@ -43,9 +93,9 @@ pub enum VerifierError {
     #[error("Execution error: {0}")]
     Execution(String),
 
-    /// Error in task implementation.
-    #[error("Task error: {0}")]
-    Task(String),
+    /// A task cannot run with the state it was given.
+    #[error(transparent)]
+    Task(#[from] TaskError),
 
     /// Error for invalid data.
     #[error("Invalid data: {0}")]
@@ -54,4 +104,90 @@ pub enum VerifierError {
     /// General IO error.
     #[error(transparent)]
     Io(#[from] io::Error),
+
+    /// Account or task bytes cannot be viewed as the expected type.
+    #[error(transparent)]
+    Cast(#[from] CastError),
+
+    /// The task on top of the stack has a type tag no known task uses.
+    #[error("Unknown task type tag {0:#010x}")]
+    UnknownTask(u32),
+
+    /// A staged frame does not hash to the value given when committing it.
+    #[error("Staged frame hash mismatch")]
+    HashMismatch,
+
+    /// Finalize was called on a job that still has tasks to run.
+    #[error("Job not finished")]
+    JobNotFinished,
+
+    /// The result account is not the one derived from the job account.
+    #[error("Invalid result account address")]
+    InvalidResultAccount,
+
+    /// The job was aborted and no longer accepts tasks, data or execution steps.
+    #[error("Job aborted with reason {0}")]
+    JobAborted(u32),
+
+    /// The account receiving the lamports of a closed job is the job account itself.
+    #[error("Invalid recipient - cannot close an account into itself")]
+    InvalidRecipient,
+
+    /// The job account is not the one derived from the authority and job id.
+    #[error("Invalid job account address")]
+    InvalidJobAccount,
+
+    /// The stack must be empty for the account to be resized.
+    #[error("Job not empty - reset it before growing the account")]
+    JobNotEmpty,
+}
+
+impl VerifierError {
+    /// Code returned in `ProgramError::Custom` when the program fails with this error
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::InvalidOwner => 0,
+            Self::AccountNotWritable => 1,
+            Self::InvalidAuthority => 2,
+            Self::MissingAuthoritySignature => 3,
+            Self::SchedulerDeserializationError => 4,
+            Self::SchedulerTaskPushError => 5,
+            Self::SchedulerExecutionError => 6,
+            Self::SchedulerDataPopError => 7,
+            Self::SchedulerSerializationError => 8,
+            Self::TryFromInt(_) => 9,
+            Self::AccountTooSmall => 10,
+            Self::InvalidMagic => 11,
+            Self::UnsupportedVersion(_) => 12,
+            Self::AlreadyInitialized => 13,
+            Self::NotInitialized => 14,
+            Self::InvalidStatus(_) => 15,
+            Self::JobFinished => 16,
+            Self::EmptyStack => 17,
+            Self::StackCapacity => 18,
+            Self::InvalidFrame => 19,
+            Self::Value(_) => 20,
+            Self::InvalidTaskLength => 21,
+            Self::Execution(_) => 22,
+            Self::Task(_) => 23,
+            Self::InvalidData(_) => 24,
+            Self::Io(_) => 25,
+            Self::Cast(_) => 26,
+            Self::UnknownTask(_) => 27,
+            Self::HashMismatch => 28,
+            Self::JobNotFinished => 29,
+            Self::InvalidResultAccount => 30,
+            Self::JobAborted(_) => 31,
+            Self::InvalidRecipient => 32,
+            Self::InvalidJobAccount => 33,
+            Self::JobNotEmpty => 34,
+        }
+    }
+}
+
+impl From<VerifierError> for ProgramError {
+    fn from(e: VerifierError) -> Self {
+        msg!("Error: {}", e);
+        ProgramError::Custom(e.code())
+    }
 }
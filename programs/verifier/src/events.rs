@@ -0,0 +1,99 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    hash::{hashv, Hash},
+    log::sol_log_data,
+    program::{set_return_data, MAX_RETURN_DATA},
+    pubkey::Pubkey,
+};
+use utils::BidirectionalStack;
+
+use crate::{error::VerifierError, state::BidirectionalStackAccount};
+
+/// Result of a job, emitted once its last task has run
+///
+/// Logged with `sol_log_data` after [`JobCompleted::EVENT`], so clients can read it
+/// from the transaction metadata instead of fetching the account. The instruction
+/// that finishes the job also sets it as return data.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JobCompleted {
+    /// The verifier account holding the job
+    pub job: Pubkey,
+    /// Scheduler steps the job took
+    pub steps: u64,
+    /// Hash of the data frames left on the stack, see [`frames_hash`]
+    pub frames_hash: [u8; 32],
+    /// The data frames themselves, from the top downwards, when they fit in the return data
+    pub frames: Option<Vec<Vec<u8>>>,
+}
+
+impl JobCompleted {
+    /// Name logged as the first field of the event
+    pub const EVENT: &'static [u8] = b"JobCompleted";
+
+    /// Describe the finished job held in `stack`
+    pub fn new(
+        job: Pubkey,
+        steps: u64,
+        stack: &BidirectionalStackAccount,
+    ) -> Result<Self, VerifierError> {
+        // Frames are borrowed from the account, the heap cannot hold copies of large results
+        let frames = stack.front_frames().collect::<Result<Vec<_>, _>>()?;
+        let mut event = Self {
+            job,
+            steps,
+            frames_hash: frames_hash(&frames).to_bytes(),
+            frames: None,
+        };
+
+        // Large results are only identified by their hash
+        let frames_length = size_of::<u32>()
+            + frames
+                .iter()
+                .map(|frame| size_of::<u32>() + frame.len())
+                .sum::<usize>();
+        if borsh::object_length(&event)? + frames_length <= MAX_RETURN_DATA {
+            event.frames = Some(frames.iter().map(|frame| frame.to_vec()).collect());
+        }
+        Ok(event)
+    }
+
+    /// Set the event as return data and log it
+    pub fn emit(&self) -> Result<(), VerifierError> {
+        let data = borsh::to_vec(self)?;
+        set_return_data(&data);
+        sol_log_data(&[Self::EVENT, &data]);
+        Ok(())
+    }
+}
+
+/// Progress made by an `ExecuteMany` instruction that left tasks to run, set as its
+/// return data
+///
+/// An instruction finishing the job returns its [`JobCompleted`] event instead.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepsExecuted {
+    /// Scheduler steps run by the instruction
+    pub steps: u32,
+}
+
+impl StepsExecuted {
+    /// Set the progress as return data
+    pub fn emit(&self) -> Result<(), VerifierError> {
+        set_return_data(&borsh::to_vec(self)?);
+        Ok(())
+    }
+}
+
+/// SHA-256 over the data frames, from the top downwards, each after its length as a `u32`
+pub fn frames_hash<F: AsRef<[u8]>>(frames: &[F]) -> Hash {
+    let lengths: Vec<[u8; 4]> = frames
+        .iter()
+        .map(|frame| (frame.as_ref().len() as u32).to_le_bytes())
+        .collect();
+    let fields: Vec<&[u8]> = lengths
+        .iter()
+        .zip(frames)
+        .flat_map(|(length, frame)| [length.as_slice(), frame.as_ref()])
+        .collect();
+    hashv(&fields)
+}
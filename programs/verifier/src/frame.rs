@@ -0,0 +1,174 @@
+use crate::error::VerifierError;
+
+/// Maximum number of bytes a frame length prefix can take
+///
+/// Lengths are encoded as LEB128 varints bounded by `u32::MAX`, which needs at most five
+/// 7-bit groups. Frames under 128 bytes get a single byte prefix.
+pub const MAX_LENGTH_SIZE: usize = 5;
+
+/// Number of bytes the length prefix of a `length`-byte frame takes
+pub const fn length_size(length: usize) -> usize {
+    let mut size = 1;
+    let mut rest = length >> 7;
+    while rest != 0 {
+        size += 1;
+        rest >>= 7;
+    }
+    size
+}
+
+/// Encode a frame length as a LEB128 varint
+///
+/// Returns the prefix buffer together with the number of bytes used. The first byte
+/// holds the least significant group, the way the prefix is read outward from a stack index.
+pub fn encode_length(length: usize) -> Result<([u8; MAX_LENGTH_SIZE], usize), VerifierError> {
+    let mut rest = u32::try_from(length)?;
+    let mut bytes = [0u8; MAX_LENGTH_SIZE];
+    let mut size = 0;
+
+    loop {
+        let group = (rest & 0x7F) as u8;
+        rest >>= 7;
+        if rest == 0 {
+            bytes[size] = group;
+            return Ok((bytes, size + 1));
+        }
+        bytes[size] = group | 0x80;
+        size += 1;
+    }
+}
+
+/// Decode a LEB128 frame length from bytes read outward from a stack index
+///
+/// Returns the length together with the number of prefix bytes consumed. Truncated,
+/// overlong and non-canonical prefixes are rejected so every length has exactly one encoding.
+pub fn decode_length(bytes: impl IntoIterator<Item = u8>) -> Result<(usize, usize), VerifierError> {
+    let mut length: u64 = 0;
+
+    for (index, byte) in bytes.into_iter().take(MAX_LENGTH_SIZE).enumerate() {
+        length |= u64::from(byte & 0x7F) << (7 * index);
+
+        if byte & 0x80 == 0 {
+            // A zero final group after the first byte could have been left out
+            if byte == 0 && index > 0 {
+                return Err(VerifierError::InvalidFrame);
+            }
+            let length = u32::try_from(length).map_err(|_| VerifierError::InvalidFrame)?;
+            return Ok((length as usize, index + 1));
+        }
+    }
+
+    Err(VerifierError::InvalidFrame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lengths on both sides of every change in prefix size
+    const BOUNDARIES: [(usize, usize); 10] = [
+        (0, 1),
+        (127, 1),
+        (128, 2),
+        ((1 << 14) - 1, 2),
+        (1 << 14, 3),
+        ((1 << 21) - 1, 3),
+        (1 << 21, 4),
+        ((1 << 28) - 1, 4),
+        (1 << 28, 5),
+        (u32::MAX as usize, 5),
+    ];
+
+    #[test]
+    fn test_length_size_boundaries() {
+        for (length, size) in BOUNDARIES {
+            assert_eq!(length_size(length), size, "length {}", length);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_boundaries() {
+        for (length, size) in BOUNDARIES {
+            let (bytes, encoded_size) = encode_length(length).unwrap();
+            assert_eq!(encoded_size, size, "length {}", length);
+
+            let decoded = decode_length(bytes[..encoded_size].iter().copied()).unwrap();
+            assert_eq!(decoded, (length, size));
+        }
+    }
+
+    #[test]
+    fn test_known_encodings() {
+        let (bytes, size) = encode_length(0).unwrap();
+        assert_eq!(&bytes[..size], &[0x00]);
+
+        let (bytes, size) = encode_length(127).unwrap();
+        assert_eq!(&bytes[..size], &[0x7F]);
+
+        let (bytes, size) = encode_length(128).unwrap();
+        assert_eq!(&bytes[..size], &[0x80, 0x01]);
+
+        let (bytes, size) = encode_length(65535).unwrap();
+        assert_eq!(&bytes[..size], &[0xFF, 0xFF, 0x03]);
+
+        let (bytes, size) = encode_length(u32::MAX as usize).unwrap();
+        assert_eq!(&bytes[..size], &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+    }
+
+    #[test]
+    fn test_decode_ignores_trailing_bytes() {
+        // Bytes beyond the prefix belong to the neighbouring frame
+        assert_eq!(decode_length([0x05, 0xFF, 0xFF]).unwrap(), (5, 1));
+        assert_eq!(decode_length([0x80, 0x01, 0x80]).unwrap(), (128, 2));
+    }
+
+    #[test]
+    fn test_encode_too_large() {
+        assert!(matches!(
+            encode_length(u32::MAX as usize + 1),
+            Err(VerifierError::TryFromInt(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        assert!(matches!(
+            decode_length([]),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(
+            decode_length([0x80]),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(
+            decode_length([0xFF, 0xFF]),
+            Err(VerifierError::InvalidFrame)
+        ));
+    }
+
+    #[test]
+    fn test_decode_overlong() {
+        // Six groups never terminate within the prefix limit
+        assert!(matches!(
+            decode_length([0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]),
+            Err(VerifierError::InvalidFrame)
+        ));
+        // Five groups holding more than 32 bits
+        assert!(matches!(
+            decode_length([0xFF, 0xFF, 0xFF, 0xFF, 0x1F]),
+            Err(VerifierError::InvalidFrame)
+        ));
+    }
+
+    #[test]
+    fn test_decode_non_canonical() {
+        assert!(matches!(
+            decode_length([0x80, 0x00]),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(
+            decode_length([0xFF, 0x80, 0x00]),
+            Err(VerifierError::InvalidFrame)
+        ));
+    }
+}
@@ -3,27 +3,143 @@ use borsh::{BorshDeserialize, BorshSerialize};
 /// Instructions supported by the verifier program
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum VerifierInstruction {
-    /// Initializes the verifier account with default values
+    /// Initializes the verifier account header and an empty stack
+    ///
+    /// Fails if the account already holds a job.
     ///
     /// Accounts expected:
     /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
     Initialize,
 
     /// Pushes a task to the verifier account's bidirectional stack
     ///
     /// Accounts expected:
     /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
     PushTask(Vec<u8>),
 
     /// Pushes data to the verifier account's bidirectional stack
     ///
     /// Accounts expected:
     /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
     PushData(Vec<u8>),
 
+    /// Pushes several tasks to the back of the stack, in order
+    ///
+    /// Same as one `PushTask` per frame, so the last frame ends up on top.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
+    PushTaskBatch(Vec<Vec<u8>>),
+
+    /// Pushes several data frames to the front of the stack, in order
+    ///
+    /// Same as one `PushData` per frame, so the last frame ends up on top.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
+    PushDataBatch(Vec<Vec<u8>>),
+
+    /// Writes part of a large data frame into the staging area above the front side
+    ///
+    /// The frame is only pushed once committed with `CommitFrame`. Chunks can be
+    /// written in any order and rewritten, so an interrupted upload can be resumed.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
+    WriteChunk { offset: u32, bytes: Vec<u8> },
+
+    /// Pushes the first `length` staged bytes as a data frame
+    ///
+    /// When `hash` is given, the staged bytes must have that SHA-256 hash.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
+    CommitFrame { length: u32, hash: Option<[u8; 32]> },
+
     /// Executes the next task in the verifier account's bidirectional stack
     ///
     /// Accounts expected:
     /// 0. `[writable]` The verifier account
     Execute,
+
+    /// Executes tasks until none are left or one of the limits is reached
+    ///
+    /// Stops after `max_steps` steps, or before a step once fewer than
+    /// `compute_margin` compute units remain. The first step always runs.
+    ///
+    /// Sets [`StepsExecuted`](crate::events::StepsExecuted) as return data while tasks
+    /// are left, or [`JobCompleted`](crate::events::JobCompleted) once the job finishes.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    ExecuteMany { max_steps: u32, compute_margin: u64 },
+
+    /// Records the outcome of a finished job and closes its account
+    ///
+    /// Writes a [`VerificationResult`](crate::state::VerificationResult) into the
+    /// result account derived from the job account and its inputs hash, then refunds
+    /// the job account's rent to the authority.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer, writable]` The authority of the job, paying for the result account
+    /// 2. `[writable]` The result account
+    /// 3. `[]` The system program
+    Finalize,
+
+    /// Clears both sides of the stack so the job can be pushed again from scratch
+    ///
+    /// The header and its authority are kept, the job goes back to `Created`.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
+    Reset,
+
+    /// Marks a pending job as failed with a reason code of the authority's choosing
+    ///
+    /// An aborted job accepts no more tasks, data or execution steps until reset.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
+    Abort { reason: u32 },
+
+    /// Zeroes the verifier account and moves all its lamports to the recipient
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer]` The authority of the job
+    /// 2. `[writable]` The account receiving the lamports
+    Close,
+
+    /// Creates and initializes the job account derived from the authority and `job_id`
+    ///
+    /// The runtime lets an instruction allocate at most
+    /// `MAX_PERMITTED_DATA_INCREASE` bytes, so larger stacks are created at that size
+    /// and extended with `GrowJob` up to `capacity`.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The job account, see `AccountHeader::find_job_address`
+    /// 1. `[signer, writable]` The authority of the job, paying for the account
+    /// 2. `[]` The system program
+    CreateJob { job_id: u64, capacity: u32 },
+
+    /// Extends an empty job account towards a stack of `capacity` bytes
+    ///
+    /// Grows the account by at most `MAX_PERMITTED_DATA_INCREASE` bytes, with the
+    /// authority topping up its rent.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The verifier account
+    /// 1. `[signer, writable]` The authority of the job, paying for the rent
+    /// 2. `[]` The system program
+    GrowJob { capacity: u32 },
 }
@@ -1,6 +1,8 @@
 // Export modules
 pub mod entrypoint;
 pub mod error;
+pub mod events;
+pub mod frame;
 pub mod instruction;
 pub mod processor;
 pub mod scheduler;
@@ -1,105 +1,659 @@
 use borsh::BorshDeserialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint::ProgramResult,
+    compute_units::sol_remaining_compute_units,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    hash::hash,
     msg,
-    program_error::ProgramError,
+    program::{invoke, invoke_signed},
     pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
 use utils::{AccountCast, BidirectionalStack};
 
-use crate::{instruction::VerifierInstruction, state::BidirectionalStackAccount};
+use crate::{
+    error::VerifierError,
+    events::{frames_hash, JobCompleted, StepsExecuted},
+    instruction::VerifierInstruction,
+    state::{
+        AccountHeader, BidirectionalStackAccount, JobStatus, Side, Verdict, VerificationResult,
+    },
+};
 
 /// Program state handler
 pub struct Processor;
 
 impl Processor {
     /// Process the initialize instruction
-    pub fn process_initialize(accounts: &[AccountInfo]) -> ProgramResult {
+    pub fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         msg!("Processing Initialize instruction");
 
-        // Get the account to initialize
+        // Get the account to initialize and the authority of the job
         let accounts_iter = &mut accounts.iter();
         let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+        if !authority.is_signer {
+            return Err(VerifierError::MissingAuthoritySignature.into());
+        }
 
-        // Initialize the bidirectional stack
+        // Write the header, refusing accounts that already hold a job
         let mut data = account.try_borrow_mut_data()?;
-        let stack_account = BidirectionalStackAccount::cast_mut(*data);
+        let (header, stack_account) = AccountHeader::split_mut(*data)?;
+        header.initialize(*authority.key)?;
 
-        // Set to default values - front_index to 0, back_index to CAPACITY
-        *stack_account = BidirectionalStackAccount::default();
+        // Reset the indices - front_index to 0, back_index to the buffer length
+        stack_account.initialize();
         msg!("Account initialized successfully");
 
         Ok(())
     }
 
     /// Process the push task instruction
-    pub fn process_push_task(accounts: &[AccountInfo], task_data: Vec<u8>) -> ProgramResult {
+    pub fn process_push_task(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        task_data: Vec<u8>,
+    ) -> ProgramResult {
         msg!("Processing PushTask instruction");
 
-        // Get the account to push task to
+        // Get the account to push task to and the authority of the job
         let accounts_iter = &mut accounts.iter();
         let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
 
         // Push the task to the bidirectional stack
         let mut data = account.try_borrow_mut_data()?;
-        let stack_account = BidirectionalStackAccount::cast_mut(*data);
+        let (header, stack_account) = load_pending_job(*data)?;
+        check_authority(header, authority)?;
 
         // Push the task data to the back of the stack
-        stack_account.push_back(&task_data).map_err(|e| {
-            msg!("Error pushing task: {:?}", e);
-            ProgramError::InvalidInstructionData
-        })?;
+        stack_account.push_back(&task_data)?;
+        header.record_input(Side::Back, &task_data);
         msg!("Task pushed successfully");
 
         Ok(())
     }
 
     /// Process the push data instruction
-    pub fn process_push_data(accounts: &[AccountInfo], data_payload: Vec<u8>) -> ProgramResult {
+    pub fn process_push_data(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        data_payload: Vec<u8>,
+    ) -> ProgramResult {
         msg!("Processing PushData instruction");
 
-        // Get the account to push data to
+        // Get the account to push data to and the authority of the job
         let accounts_iter = &mut accounts.iter();
         let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
 
         // Push the data to the bidirectional stack
         let mut data = account.try_borrow_mut_data()?;
-        let stack_account = BidirectionalStackAccount::cast_mut(*data);
+        let (header, stack_account) = load_pending_job(*data)?;
+        check_authority(header, authority)?;
 
         // Push the data to the front of the stack
-        stack_account.push_front(&data_payload).map_err(|e| {
-            msg!("Error pushing data: {:?}", e);
-            ProgramError::InvalidInstructionData
-        })?;
+        stack_account.push_front(&data_payload)?;
+        header.record_input(Side::Front, &data_payload);
         msg!("Data pushed successfully");
 
         Ok(())
     }
 
+    /// Process the push task batch instruction
+    pub fn process_push_task_batch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        tasks: Vec<Vec<u8>>,
+    ) -> ProgramResult {
+        msg!("Processing PushTaskBatch instruction");
+
+        // Get the account to push tasks to and the authority of the job
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, stack_account) = load_pending_job(*data)?;
+        check_authority(header, authority)?;
+
+        // Push every task in order, so the last one is executed first
+        for task_data in &tasks {
+            stack_account.push_back(task_data)?;
+            header.record_input(Side::Back, task_data);
+        }
+        msg!("{} tasks pushed successfully", tasks.len());
+
+        Ok(())
+    }
+
+    /// Process the push data batch instruction
+    pub fn process_push_data_batch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        frames: Vec<Vec<u8>>,
+    ) -> ProgramResult {
+        msg!("Processing PushDataBatch instruction");
+
+        // Get the account to push data to and the authority of the job
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, stack_account) = load_pending_job(*data)?;
+        check_authority(header, authority)?;
+
+        // Push every frame in order, so the last one ends up on top
+        for data_payload in &frames {
+            stack_account.push_front(data_payload)?;
+            header.record_input(Side::Front, data_payload);
+        }
+        msg!("{} data frames pushed successfully", frames.len());
+
+        Ok(())
+    }
+
+    /// Process the write chunk instruction
+    pub fn process_write_chunk(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        offset: u32,
+        bytes: Vec<u8>,
+    ) -> ProgramResult {
+        msg!("Processing WriteChunk instruction");
+
+        // Get the account to stage the chunk in and the authority of the job
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, stack_account) = load_pending_job(*data)?;
+        check_authority(header, authority)?;
+
+        stack_account.write_staged(
+            usize::try_from(offset).map_err(VerifierError::from)?,
+            &bytes,
+        )?;
+        msg!(
+            "Chunk of {} bytes written at offset {}",
+            bytes.len(),
+            offset
+        );
+
+        Ok(())
+    }
+
+    /// Process the commit frame instruction
+    pub fn process_commit_frame(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        length: u32,
+        expected_hash: Option<[u8; 32]>,
+    ) -> ProgramResult {
+        msg!("Processing CommitFrame instruction");
+
+        // Get the account holding the staged frame and the authority of the job
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, stack_account) = load_pending_job(*data)?;
+        check_authority(header, authority)?;
+
+        // Check the staged bytes before they become a frame
+        let length = usize::try_from(length).map_err(VerifierError::from)?;
+        let staged = stack_account.staged(length)?;
+        if let Some(expected_hash) = expected_hash {
+            if hash(staged).to_bytes() != expected_hash {
+                return Err(VerifierError::HashMismatch.into());
+            }
+        }
+        header.record_input(Side::Front, staged);
+        stack_account.commit_staged(length)?;
+        msg!("Frame of {} bytes committed", length);
+
+        Ok(())
+    }
+
     /// Process the execute instruction
-    pub fn process_execute(accounts: &[AccountInfo]) -> ProgramResult {
+    pub fn process_execute(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         msg!("Processing Execute instruction");
 
         // Get the account to execute task from
         let accounts_iter = &mut accounts.iter();
         let account = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
 
         // Execute the next task in the stack
         let mut data = account.try_borrow_mut_data()?;
-        let stack_account = BidirectionalStackAccount::cast_mut(*data);
+        let (header, stack_account) = load_pending_job(*data)?;
 
         // Execute the task
-        stack_account.execute();
+        stack_account.execute()?;
+        record_steps(account.key, header, stack_account, 1)?;
         msg!("Task executed successfully");
 
         Ok(())
     }
+
+    /// Process the execute many instruction
+    pub fn process_execute_many(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_steps: u32,
+        compute_margin: u64,
+    ) -> ProgramResult {
+        msg!("Processing ExecuteMany instruction");
+
+        // Get the account to execute tasks from
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, stack_account) = load_pending_job(*data)?;
+
+        // Keep stepping while enough compute units are left for another step
+        let steps = stack_account.execute_many(max_steps, || {
+            sol_remaining_compute_units() >= compute_margin
+        })?;
+        record_steps(account.key, header, stack_account, steps)?;
+        // A finished job already returns its `JobCompleted` event
+        if !stack_account.is_empty_back() {
+            StepsExecuted { steps }.emit()?;
+        }
+        msg!("Executed {} steps", steps);
+
+        Ok(())
+    }
+
+    /// Process the finalize instruction
+    pub fn process_finalize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        msg!("Processing Finalize instruction");
+
+        // Get the finished job, its authority, the result account and the system program
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        let result_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        // Only a finished job has a result to record
+        let (inputs_commitment, outputs_commitment) = {
+            let data = account.try_borrow_data()?;
+            let (header, stack_account) = AccountHeader::split(&data)?;
+            header.validate()?;
+            check_authority(header, authority)?;
+            if header.status()? != JobStatus::Finished {
+                return Err(VerifierError::JobNotFinished.into());
+            }
+            let frames = stack_account
+                .front_frames()
+                .collect::<Result<Vec<_>, _>>()?;
+            (header.inputs_hash, frames_hash(&frames).to_bytes())
+        };
+
+        // Create the result account at the address derived from the job and its inputs
+        let (result_address, bump) =
+            VerificationResult::find_address(program_id, account.key, &inputs_commitment);
+        if *result_account.key != result_address {
+            return Err(VerifierError::InvalidResultAccount.into());
+        }
+        // Only this program can own an account at the address, which then holds the
+        // result of an earlier run of the same job with the same inputs
+        if result_account.owner != program_id {
+            create_derived_account(
+                program_id,
+                authority,
+                result_account,
+                system_program,
+                VerificationResult::SIZE,
+                &[
+                    VerificationResult::SEED,
+                    account.key.as_ref(),
+                    &inputs_commitment,
+                    &[bump],
+                ],
+            )?;
+        }
+
+        let mut result_data = result_account.try_borrow_mut_data()?;
+        *VerificationResult::cast_mut(*result_data).map_err(VerifierError::from)? =
+            VerificationResult {
+                magic: VerificationResult::MAGIC,
+                version: VerificationResult::VERSION,
+                verdict: Verdict::Accepted as u8,
+                bump,
+                _padding: [0; 2],
+                job: *account.key,
+                slot: Clock::get()?.slot,
+                inputs_commitment,
+                outputs_commitment,
+            };
+
+        // Close the job account, refunding its rent to the authority
+        close_account(account, authority)?;
+        msg!("Job finalized into {}", result_address);
+
+        Ok(())
+    }
+
+    /// Process the reset instruction
+    pub fn process_reset(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        msg!("Processing Reset instruction");
+
+        // Get the account to reset and the authority of the job
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, stack_account) = AccountHeader::split_mut(*data)?;
+        header.validate()?;
+        check_authority(header, authority)?;
+
+        // Empty both sides, jobs in any state can be started over
+        header.reset();
+        stack_account.initialize();
+        msg!("Job reset successfully");
+
+        Ok(())
+    }
+
+    /// Process the abort instruction
+    pub fn process_abort(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reason: u32,
+    ) -> ProgramResult {
+        msg!("Processing Abort instruction");
+
+        // Get the account to abort and the authority of the job
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, _) = load_pending_job(*data)?;
+        check_authority(header, authority)?;
+
+        header.abort(reason);
+        msg!("Job aborted with reason {}", reason);
+
+        Ok(())
+    }
+
+    /// Process the close instruction
+    pub fn process_close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        msg!("Processing Close instruction");
+
+        // Get the account to close, the authority of the job and the recipient
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        let recipient = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+        if account.key == recipient.key {
+            return Err(VerifierError::InvalidRecipient.into());
+        }
+
+        {
+            let data = account.try_borrow_data()?;
+            let (header, _) = AccountHeader::split(&data)?;
+            header.validate()?;
+            check_authority(header, authority)?;
+        }
+
+        close_account(account, recipient)?;
+        msg!("Account closed into {}", recipient.key);
+
+        Ok(())
+    }
+
+    /// Process the create job instruction
+    pub fn process_create_job(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        job_id: u64,
+        capacity: u32,
+    ) -> ProgramResult {
+        msg!("Processing CreateJob instruction");
+
+        // Get the job account, its authority and the system program
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        if !authority.is_signer {
+            return Err(VerifierError::MissingAuthoritySignature.into());
+        }
+
+        let (job_address, bump) =
+            AccountHeader::find_job_address(program_id, authority.key, job_id);
+        if *account.key != job_address {
+            return Err(VerifierError::InvalidJobAccount.into());
+        }
+
+        // Allocate what one instruction may, GrowJob extends the account further
+        let capacity = usize::try_from(capacity).map_err(VerifierError::from)?;
+        let space = AccountHeader::account_space(capacity).min(MAX_PERMITTED_DATA_INCREASE);
+        create_derived_account(
+            program_id,
+            authority,
+            account,
+            system_program,
+            space,
+            &[
+                AccountHeader::JOB_SEED,
+                authority.key.as_ref(),
+                &job_id.to_le_bytes(),
+                &[bump],
+            ],
+        )?;
+        check_verifier_account(program_id, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        let (header, stack_account) = AccountHeader::split_mut(*data)?;
+        header.initialize(*authority.key)?;
+        stack_account.initialize();
+        msg!("Job {} created at {}", job_id, job_address);
+
+        Ok(())
+    }
+
+    /// Process the grow job instruction
+    pub fn process_grow_job(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        capacity: u32,
+    ) -> ProgramResult {
+        msg!("Processing GrowJob instruction");
+
+        // Get the account to grow, the authority of the job and the system program
+        let accounts_iter = &mut accounts.iter();
+        let account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        check_verifier_account(program_id, account)?;
+
+        // Frames sit at both ends of the buffer, so only an empty stack can be moved
+        {
+            let data = account.try_borrow_data()?;
+            let (header, stack_account) = AccountHeader::split(&data)?;
+            header.validate()?;
+            check_authority(header, authority)?;
+            if !stack_account.is_empty_front() || !stack_account.is_empty_back() {
+                return Err(VerifierError::JobNotEmpty.into());
+            }
+        }
+
+        let capacity = usize::try_from(capacity).map_err(VerifierError::from)?;
+        let space = AccountHeader::account_space(capacity)
+            .min(account.data_len() + MAX_PERMITTED_DATA_INCREASE);
+        if space <= account.data_len() {
+            msg!("Account already holds {} bytes", account.data_len());
+            return Ok(());
+        }
+
+        // Top up the rent before the account grows
+        let rent = Rent::get()?.minimum_balance(space);
+        if account.lamports() < rent {
+            invoke(
+                &system_instruction::transfer(
+                    authority.key,
+                    account.key,
+                    rent - account.lamports(),
+                ),
+                &[authority.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        account.realloc(space, true)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        AccountHeader::split_mut(*data)?.1.initialize();
+        msg!("Account grown to {} bytes", space);
+
+        Ok(())
+    }
+}
+
+/// Create an account of `space` bytes owned by this program at a program-derived address
+///
+/// Anyone can send lamports to a derivable address, and `create_account` refuses
+/// accounts that already hold some. Such an account is topped up to the rent, then
+/// allocated and assigned with the same seeds instead.
+fn create_derived_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                rent,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            &[seeds],
+        );
+    }
+
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, top_up),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+/// Zero the account data and move all its lamports to the recipient
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    account.try_borrow_mut_data()?.fill(0);
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **recipient.try_borrow_mut_lamports()? += lamports;
+
+    Ok(())
+}
+
+/// Count executed steps and update the job status
+///
+/// The job is done once no tasks are left on the back side, its result is then
+/// emitted as a [`JobCompleted`] event.
+fn record_steps(
+    job: &Pubkey,
+    header: &mut AccountHeader,
+    stack_account: &BidirectionalStackAccount,
+    steps: u32,
+) -> Result<(), VerifierError> {
+    header.steps += u64::from(steps);
+    if stack_account.is_empty_back() {
+        header.set_status(JobStatus::Finished);
+        JobCompleted::new(*job, header.steps, stack_account)?.emit()?;
+    } else {
+        header.set_status(JobStatus::Running);
+    }
+
+    Ok(())
+}
+
+/// Check that the verifier account belongs to this program and can be written
+fn check_verifier_account(program_id: &Pubkey, account: &AccountInfo) -> Result<(), VerifierError> {
+    if account.owner != program_id {
+        return Err(VerifierError::InvalidOwner);
+    }
+    if !account.is_writable {
+        return Err(VerifierError::AccountNotWritable);
+    }
+
+    Ok(())
+}
+
+/// Check that the job authority recorded in the header signed the instruction
+fn check_authority(header: &AccountHeader, authority: &AccountInfo) -> Result<(), VerifierError> {
+    if header.authority != *authority.key {
+        return Err(VerifierError::InvalidAuthority);
+    }
+    if !authority.is_signer {
+        return Err(VerifierError::MissingAuthoritySignature);
+    }
+
+    Ok(())
+}
+
+/// Split the account data and validate the header before the stack is touched
+///
+/// Finished jobs are rejected, their results stay on the stack untouched. Aborted jobs
+/// are rejected until reset.
+fn load_pending_job(
+    data: &mut [u8],
+) -> Result<(&mut AccountHeader, &mut BidirectionalStackAccount), VerifierError> {
+    let (header, stack_account) = AccountHeader::split_mut(data)?;
+    header.validate()?;
+    match header.status()? {
+        JobStatus::Finished => return Err(VerifierError::JobFinished),
+        JobStatus::Failed => return Err(VerifierError::JobAborted(header.failure_code)),
+        JobStatus::Created | JobStatus::Running => {}
+    }
+
+    Ok((header, stack_account))
 }
 
 /// Instruction processor
 pub fn process_instruction(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
@@ -110,13 +664,41 @@ pub fn process_instruction(
 
     // Process the instruction
     match instruction {
-        VerifierInstruction::Initialize => Processor::process_initialize(accounts),
+        VerifierInstruction::Initialize => Processor::process_initialize(program_id, accounts),
         VerifierInstruction::PushTask(task_data) => {
-            Processor::process_push_task(accounts, task_data)
+            Processor::process_push_task(program_id, accounts, task_data)
         }
         VerifierInstruction::PushData(data_payload) => {
-            Processor::process_push_data(accounts, data_payload)
+            Processor::process_push_data(program_id, accounts, data_payload)
+        }
+        VerifierInstruction::PushTaskBatch(tasks) => {
+            Processor::process_push_task_batch(program_id, accounts, tasks)
+        }
+        VerifierInstruction::PushDataBatch(frames) => {
+            Processor::process_push_data_batch(program_id, accounts, frames)
+        }
+        VerifierInstruction::WriteChunk { offset, bytes } => {
+            Processor::process_write_chunk(program_id, accounts, offset, bytes)
+        }
+        VerifierInstruction::CommitFrame { length, hash } => {
+            Processor::process_commit_frame(program_id, accounts, length, hash)
+        }
+        VerifierInstruction::Execute => Processor::process_execute(program_id, accounts),
+        VerifierInstruction::ExecuteMany {
+            max_steps,
+            compute_margin,
+        } => Processor::process_execute_many(program_id, accounts, max_steps, compute_margin),
+        VerifierInstruction::Finalize => Processor::process_finalize(program_id, accounts),
+        VerifierInstruction::Reset => Processor::process_reset(program_id, accounts),
+        VerifierInstruction::Abort { reason } => {
+            Processor::process_abort(program_id, accounts, reason)
+        }
+        VerifierInstruction::Close => Processor::process_close(program_id, accounts),
+        VerifierInstruction::CreateJob { job_id, capacity } => {
+            Processor::process_create_job(program_id, accounts, job_id, capacity)
+        }
+        VerifierInstruction::GrowJob { capacity } => {
+            Processor::process_grow_job(program_id, accounts, capacity)
         }
-        VerifierInstruction::Execute => Processor::process_execute(accounts),
     }
 }
@@ -1,21 +1,120 @@
-use crate::state::BidirectionalStackAccount;
-use utils::{BidirectionalStack, Executable, Scheduler};
+use crate::{error::VerifierError, state::BidirectionalStackAccount};
+use utils::{
+    assert_unique_tags, BidirectionalStack, Decoder, Executable, Scheduler, TaskInfo, TaskVisitor,
+};
 
-// Include the generated dispatch code
-include!(concat!(env!("OUT_DIR"), "/verifier_executable_dispatch.rs"));
+include!("task_crates.rs");
+
+/// Task lists of every crate the verifier can execute tasks from
+pub const TASKS: &[&[TaskInfo]] = task_crates!(task_lists);
+
+const _: () = assert_unique_tags(TASKS);
+
+/// JSON manifest of the registered tasks, with the path, identifier, version, type tag,
+/// size and legacy tags of each
+///
+/// Generated by the build script from the same task lists as [`TASKS`].
+pub const TASK_MANIFEST: &str = include_str!(concat!(env!("OUT_DIR"), "/task_manifest.json"));
 
 impl Scheduler for BidirectionalStackAccount {}
 
+/// Run one step of the task on top of the back side of the stack
+///
+/// Task frames sit at arbitrary offsets in the account data, so the task is copied
+/// out of its frame, executed, and its updated state is copied back afterwards.
+/// A frame queued under a legacy tag is read with its `decode` and rewritten in the
+/// current layout, so later steps take the regular path.
+fn execute_task<T: Executable>(
+    stack: &mut BidirectionalStackAccount,
+    decode: Decoder<T>,
+) -> Result<(Vec<Vec<u8>>, bool), VerifierError> {
+    let frame = stack.borrow_back()?;
+    let is_current = frame.starts_with(&T::TYPE_TAG.to_be_bytes());
+    let mut task = decode(frame.get(4..).ok_or(VerifierError::InvalidTaskLength)?)?;
+
+    let tasks = task.execute(stack)?;
+    let is_finished = task.is_finished();
+
+    if is_current {
+        stack
+            .borrow_mut_back()?
+            .get_mut(4..)
+            .ok_or(VerifierError::InvalidTaskLength)?
+            .copy_from_slice(task.as_bytes());
+    } else {
+        stack.pop_back()?;
+        stack.push_back(&task.to_vec_with_type_tag())?;
+    }
+    Ok((tasks, is_finished))
+}
+
+/// Hand `visitor` to the dispatcher of each crate in turn, until one knows `type_tag`
+macro_rules! dispatch {
+    ($($krate:ident),+) => {
+        fn dispatch<V: TaskVisitor>(type_tag: u32, visitor: V) -> Result<V::Output, V> {
+            Err(visitor)$(.or_else(|visitor| $krate::dispatch(type_tag, visitor)))+
+        }
+    };
+}
+
+task_crates!(dispatch);
+
+/// Runs one step of the task type picked by the dispatchers
+struct ExecuteTask<'a>(&'a mut BidirectionalStackAccount);
+
+impl TaskVisitor for ExecuteTask<'_> {
+    type Output = Result<(Vec<Vec<u8>>, bool), VerifierError>;
+
+    fn visit<T: Executable>(self, decode: Decoder<T>) -> Self::Output {
+        execute_task(self.0, decode)
+    }
+}
+
+/// Run one step of the task on top of the back side, whichever crate registered it
+pub fn execute(
+    stack: &mut BidirectionalStackAccount,
+) -> Result<(Vec<Vec<u8>>, bool), VerifierError> {
+    let type_tag = stack
+        .borrow_back()?
+        .first_chunk::<4>()
+        .map(|tag| u32::from_be_bytes(*tag))
+        .ok_or(VerifierError::InvalidTaskLength)?;
+
+    dispatch(type_tag, ExecuteTask(stack)).unwrap_or(Err(VerifierError::UnknownTask(type_tag)))
+}
+
 impl BidirectionalStackAccount {
-    pub fn execute(&mut self) {
-        let (tasks, is_finished) = execute(self);
+    pub fn execute(&mut self) -> Result<(), VerifierError> {
+        let (tasks, is_finished) = execute(self)?;
 
         if is_finished {
-            self.pop_back();
+            self.pop_back()?;
         }
 
         for task in tasks.iter().rev() {
-            let _ = self.push_back(task);
+            self.push_back(task)?;
+        }
+
+        Ok(())
+    }
+    /// Run steps until no tasks are left, `max_steps` have run or `has_budget` says stop
+    ///
+    /// The budget is checked before every step but the first, so each call makes
+    /// progress. Returns the number of steps that ran.
+    pub fn execute_many(
+        &mut self,
+        max_steps: u32,
+        mut has_budget: impl FnMut() -> bool,
+    ) -> Result<u32, VerifierError> {
+        let mut steps = 0;
+        while steps < max_steps && !self.is_empty_back() {
+            if steps > 0 && !has_budget() {
+                break;
+            }
+            self.execute()?;
+            steps += 1;
         }
+
+        Ok(steps)
     }
 }
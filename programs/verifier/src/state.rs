@@ -1,477 +1,1437 @@
-use utils::{AccountCast, BidirectionalStack};
+use std::alloc::{alloc_zeroed, handle_alloc_error, Layout};
+use std::any::type_name;
+use std::iter;
+use std::mem::{align_of, size_of};
+use std::ops::Range;
+use std::ptr;
+
+use solana_program::{hash::hashv, pubkey::Pubkey};
+use utils::{AccountCast, BidirectionalStack, CastError, Pod};
 
 use crate::error::VerifierError;
+use crate::frame::{decode_length, encode_length};
+
+/// Progress of the verification job held in an account
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Initialized and accepting tasks and data, nothing executed yet
+    Created = 1,
+    /// At least one step executed and tasks are still pending
+    Running = 2,
+    /// Every task has been executed, the results are on the front side
+    Finished = 3,
+    /// Abandoned by the authority, the reason is kept in [`AccountHeader::failure_code`]
+    Failed = 4,
+}
 
-const CAPACITY: usize = 1024;
-const LENGTH_SIZE: usize = 2;
+impl TryFrom<u8> for JobStatus {
+    type Error = VerifierError;
 
-/// Define the type of state stored in accounts
-#[derive(Debug)]
-pub struct BidirectionalStackAccount {
-    pub front_index: usize,
-    pub back_index: usize,
-    pub buffer: [u8; CAPACITY],
-}
-impl Default for BidirectionalStackAccount {
-    fn default() -> Self {
-        Self {
-            front_index: 0,
-            back_index: CAPACITY,
-            buffer: [0; CAPACITY],
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Created),
+            2 => Ok(Self::Running),
+            3 => Ok(Self::Finished),
+            4 => Ok(Self::Failed),
+            _ => Err(VerifierError::InvalidStatus(value)),
         }
     }
 }
 
-impl AccountCast for BidirectionalStackAccount {}
+/// Fixed header at the start of every verifier account
+///
+/// The stack follows right after the header. The header records which layout the
+/// account bytes follow and who is allowed to drive the job stored in them.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountHeader {
+    pub magic: [u8; 8],
+    pub version: u32,
+    pub initialized: u8,
+    pub status: u8,
+    pub _padding: [u8; 2],
+    pub authority: Pubkey,
+    /// Scheduler steps executed for the job so far
+    pub steps: u64,
+    /// Running hash over every task and data frame pushed to the job, see [`Self::record_input`]
+    pub inputs_hash: [u8; 32],
+    /// Reason code given when the job was aborted, zero otherwise
+    pub failure_code: u32,
+    pub _reserved: [u8; 4],
+}
 
-impl BidirectionalStack for BidirectionalStackAccount {
-    type Error = VerifierError;
+unsafe impl Pod for AccountHeader {}
 
-    fn push_front(&mut self, data: &[u8]) -> Result<(), Self::Error> {
-        for byte in data {
-            self.buffer[self.front_index] = *byte;
-            self.front_index = self.front_index.saturating_add(1);
-        }
+impl AccountCast for AccountHeader {}
 
-        let data_length = data.len();
-        for i in 0..LENGTH_SIZE {
-            self.buffer[self.front_index] = ((data_length >> (i * 8)) & 0xFF).try_into()?;
-            self.front_index = self.front_index.saturating_add(1);
-        }
+impl AccountHeader {
+    /// Size of the header in the account data
+    pub const SIZE: usize = size_of::<Self>();
 
-        Ok(())
+    /// Marks the account data as a verifier account
+    pub const MAGIC: [u8; 8] = *b"VRFSTACK";
+
+    /// Layout version of the header and the stack that follows it
+    pub const VERSION: u32 = 4;
+
+    /// Seed prefix of job account addresses
+    pub const JOB_SEED: &'static [u8] = b"job";
+
+    /// Account space needed for a header and a stack whose buffer holds `capacity` bytes
+    pub const fn account_space(capacity: usize) -> usize {
+        Self::SIZE + BidirectionalStackAccount::space(capacity)
     }
 
-    fn push_back(&mut self, data: &[u8]) -> Result<(), Self::Error> {
-        for byte in data.iter().rev() {
-            self.back_index = self.back_index.saturating_sub(1);
-            self.buffer[self.back_index] = *byte;
+    /// Address and bump seed of the job account `job_id` of `authority`
+    pub fn find_job_address(program_id: &Pubkey, authority: &Pubkey, job_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::JOB_SEED, authority.as_ref(), &job_id.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    /// Split account data into the header and the stack view behind it
+    pub fn split(data: &[u8]) -> Result<(&Self, &BidirectionalStackAccount), VerifierError> {
+        if data.len() < Self::account_space(0) {
+            return Err(VerifierError::AccountTooSmall);
         }
+        let (header, stack) = data.split_at(Self::SIZE);
+        Ok((Self::cast(header)?, BidirectionalStackAccount::cast(stack)?))
+    }
 
-        let data_length = data.len();
-        for i in 0..LENGTH_SIZE {
-            self.back_index = self.back_index.saturating_sub(1);
-            self.buffer[self.back_index] = ((data_length >> (i * 8)) & 0xFF).try_into()?;
+    /// Split mutable account data into the header and the stack view behind it
+    pub fn split_mut(
+        data: &mut [u8],
+    ) -> Result<(&mut Self, &mut BidirectionalStackAccount), VerifierError> {
+        if data.len() < Self::account_space(0) {
+            return Err(VerifierError::AccountTooSmall);
         }
+        let (header, stack) = data.split_at_mut(Self::SIZE);
+        Ok((
+            Self::cast_mut(header)?,
+            BidirectionalStackAccount::cast_mut(stack)?,
+        ))
+    }
 
+    /// Write a fresh header for a job driven by `authority`
+    ///
+    /// Refuses to touch an account that already holds a job, so a half-finished
+    /// verification cannot be wiped by initializing it again.
+    pub fn initialize(&mut self, authority: Pubkey) -> Result<(), VerifierError> {
+        if self.initialized != 0 {
+            return Err(VerifierError::AlreadyInitialized);
+        }
+
+        *self = Self {
+            magic: Self::MAGIC,
+            version: Self::VERSION,
+            initialized: 1,
+            status: JobStatus::Created as u8,
+            _padding: [0; 2],
+            authority,
+            steps: 0,
+            inputs_hash: [0; 32],
+            failure_code: 0,
+            _reserved: [0; 4],
+        };
         Ok(())
     }
 
-    fn pop_front(&mut self) {
-        let mut data_length = 0_usize;
-        for _ in 0..LENGTH_SIZE {
-            self.front_index = self.front_index.saturating_sub(1);
-            let x: usize = self.buffer[self.front_index].into();
-            data_length = (data_length << 8) | x;
-        }
+    /// Start the job over, keeping its authority
+    pub fn reset(&mut self) {
+        self.set_status(JobStatus::Created);
+        self.steps = 0;
+        self.inputs_hash = [0; 32];
+        self.failure_code = 0;
+    }
 
-        self.front_index = self.front_index.saturating_sub(data_length);
+    /// Mark the job as failed for `reason`
+    pub fn abort(&mut self, reason: u32) {
+        self.set_status(JobStatus::Failed);
+        self.failure_code = reason;
     }
 
-    fn pop_back(&mut self) {
-        let mut data_length = 0_usize;
-        for _ in 0..LENGTH_SIZE {
-            let x: usize = self.buffer[self.back_index].into();
-            data_length = (data_length << 8) | x;
-            self.back_index = self.back_index.saturating_add(1);
+    /// Check that the account holds an initialized job in the current layout
+    pub fn validate(&self) -> Result<(), VerifierError> {
+        if self.initialized == 0 {
+            return Err(VerifierError::NotInitialized);
+        }
+        if self.magic != Self::MAGIC {
+            return Err(VerifierError::InvalidMagic);
         }
+        if self.version != Self::VERSION {
+            return Err(VerifierError::UnsupportedVersion(self.version));
+        }
+        self.status()?;
+        Ok(())
+    }
 
-        self.back_index = self.back_index.saturating_add(data_length);
+    pub fn status(&self) -> Result<JobStatus, VerifierError> {
+        JobStatus::try_from(self.status)
     }
 
-    fn borrow_front(&self) -> &[u8] {
-        let mut data_length = 0_usize;
-        for i in 1..=LENGTH_SIZE {
-            let x: usize = self.buffer[self.front_index.saturating_sub(i)].into();
-            data_length = (data_length << 8) | x;
-        }
+    pub fn set_status(&mut self, status: JobStatus) {
+        self.status = status as u8;
+    }
 
-        &self.buffer[self.front_index.saturating_sub(data_length + LENGTH_SIZE)
-            ..self.front_index.saturating_sub(LENGTH_SIZE)]
+    /// Fold a frame pushed to the job into the inputs hash
+    ///
+    /// The hash is SHA-256 over the previous hash, the side the frame was pushed to,
+    /// the frame length as a `u32` and the frame, so it commits to every input in order.
+    pub fn record_input(&mut self, side: Side, frame: &[u8]) {
+        self.inputs_hash = hashv(&[
+            &self.inputs_hash,
+            &[side as u8],
+            &(frame.len() as u32).to_le_bytes(),
+            frame,
+        ])
+        .to_bytes();
     }
+}
 
-    fn borrow_back(&self) -> &[u8] {
-        let mut data_length = 0_usize;
-        for i in 0..LENGTH_SIZE {
-            let x: usize = self.buffer[self.back_index.saturating_add(i)].into();
-            data_length = (data_length << 8) | x;
-        }
+/// Side of the stack a frame is pushed to
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Data frames, read and written by tasks
+    Front = 0,
+    /// Tasks waiting to be executed
+    Back = 1,
+}
+
+/// Verdict recorded for a finalized job
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Every task ran to completion
+    ///
+    /// Tasks reject their input by failing, which reverts the step, so a job that
+    /// finished has been accepted.
+    Accepted = 1,
+}
+
+/// Outcome of a job, kept in a program-derived account after the job account is closed
+///
+/// The account address is derived from [`VerificationResult::SEED`], the job account and
+/// the inputs hash of the job, so a job account created again for other inputs gets a
+/// result account of its own. Finalizing the same job with the same inputs again
+/// rewrites the earlier result.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationResult {
+    pub magic: [u8; 8],
+    pub version: u32,
+    pub verdict: u8,
+    pub bump: u8,
+    pub _padding: [u8; 2],
+    /// The job account the result was recorded for
+    pub job: Pubkey,
+    /// Slot the job was finalized in
+    pub slot: u64,
+    /// Inputs hash of the job, see [`AccountHeader::record_input`]
+    pub inputs_commitment: [u8; 32],
+    /// Hash of the data frames the job left, see [`frames_hash`](crate::events::frames_hash)
+    pub outputs_commitment: [u8; 32],
+}
+
+unsafe impl Pod for VerificationResult {}
+
+impl AccountCast for VerificationResult {}
+
+impl VerificationResult {
+    /// Size of the result account data
+    pub const SIZE: usize = size_of::<Self>();
 
-        &self.buffer[self.back_index.saturating_add(LENGTH_SIZE)
-            ..self.back_index.saturating_add(LENGTH_SIZE + data_length)]
+    /// Marks the account data as a verification result
+    pub const MAGIC: [u8; 8] = *b"VRFRSULT";
+
+    /// Layout version of the result
+    pub const VERSION: u32 = 1;
+
+    /// Seed prefix of result account addresses
+    pub const SEED: &'static [u8] = b"result";
+
+    /// Address and bump seed of the result account for `job` run with `inputs_hash`
+    pub fn find_address(program_id: &Pubkey, job: &Pubkey, inputs_hash: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, job.as_ref(), inputs_hash], program_id)
     }
+}
 
-    fn borrow_mut_front(&mut self) -> &mut [u8] {
-        let mut data_length = 0_usize;
-        for i in 1..=LENGTH_SIZE {
-            let x: usize = self.buffer[self.front_index.saturating_sub(i)].into();
-            data_length = (data_length << 8) | x;
-        }
+/// Define the type of state stored in accounts
+///
+/// The struct is a zero-copy view over the account data behind the [`AccountHeader`]:
+/// `front_index`/`back_index` followed by a buffer covering the rest of the account,
+/// so the stack grows with the account instead of being capped at a fixed size.
+#[repr(C)]
+pub struct BidirectionalStackAccount {
+    pub front_index: usize,
+    pub back_index: usize,
+    pub buffer: [u8],
+}
 
-        &mut self.buffer[self.front_index.saturating_sub(data_length + LENGTH_SIZE)
-            ..self.front_index.saturating_sub(LENGTH_SIZE)]
+impl BidirectionalStackAccount {
+    /// Size of the `front_index`/`back_index` header in front of the buffer
+    pub const HEADER_SIZE: usize = 2 * size_of::<usize>();
+
+    /// Account space needed for a stack whose buffer holds `capacity` bytes
+    pub const fn space(capacity: usize) -> usize {
+        Self::HEADER_SIZE + capacity
     }
 
-    fn borrow_mut_back(&mut self) -> &mut [u8] {
-        let mut data_length = 0_usize;
-        for i in 0..LENGTH_SIZE {
-            let x: usize = self.buffer[self.back_index.saturating_add(i)].into();
-            data_length = (data_length << 8) | x;
+    /// Check that account data can hold the indices and is aligned for them
+    fn check_cast(data: &[u8]) -> Result<(), CastError> {
+        if data.len() < Self::HEADER_SIZE {
+            return Err(CastError::InvalidLength {
+                type_name: type_name::<Self>(),
+                expected: Self::HEADER_SIZE,
+                found: data.len(),
+            });
+        }
+        if !(data.as_ptr() as usize).is_multiple_of(align_of::<usize>()) {
+            return Err(CastError::Misaligned {
+                type_name: type_name::<Self>(),
+                align: align_of::<usize>(),
+            });
         }
+        Ok(())
+    }
 
-        &mut self.buffer[self.back_index.saturating_add(LENGTH_SIZE)
-            ..self.back_index.saturating_add(LENGTH_SIZE + data_length)]
+    /// Cast account data to an immutable stack view
+    pub fn cast(data: &[u8]) -> Result<&Self, CastError> {
+        Self::check_cast(data)?;
+        let ptr = ptr::slice_from_raw_parts(data.as_ptr(), data.len() - Self::HEADER_SIZE);
+        Ok(unsafe { &*(ptr as *const Self) })
     }
 
-    fn is_empty_front(&self) -> bool {
-        self.front_index == 0
+    /// Cast account data to a mutable stack view
+    pub fn cast_mut(data: &mut [u8]) -> Result<&mut Self, CastError> {
+        Self::check_cast(data)?;
+        let ptr = ptr::slice_from_raw_parts_mut(data.as_mut_ptr(), data.len() - Self::HEADER_SIZE);
+        Ok(unsafe { &mut *(ptr as *mut Self) })
     }
 
-    fn is_empty_back(&self) -> bool {
-        self.back_index == CAPACITY
+    /// Allocate an initialized stack with a `capacity`-byte buffer, for use off-chain
+    pub fn with_capacity(capacity: usize) -> Box<Self> {
+        let layout = Layout::from_size_align(Self::space(capacity), align_of::<usize>())
+            .expect("stack capacity overflows the address space")
+            .pad_to_align();
+
+        let mut stack = unsafe {
+            let data = alloc_zeroed(layout);
+            if data.is_null() {
+                handle_alloc_error(layout);
+            }
+            Box::from_raw(ptr::slice_from_raw_parts_mut(data, capacity) as *mut Self)
+        };
+        stack.initialize();
+        stack
+    }
+
+    /// Reset both sides of the stack, sizing the back side by the buffer length
+    pub fn initialize(&mut self) {
+        self.front_index = 0;
+        self.back_index = self.buffer.len();
+    }
+
+    /// Number of bytes available to both sides of the stack together
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::state::{BidirectionalStackAccount, CAPACITY};
-    use utils::BidirectionalStack;
+impl BidirectionalStackAccount {
+    /// Bytes still free between the front and back sides of the stack
+    fn free_space(&self) -> Result<usize, VerifierError> {
+        if self.back_index > self.buffer.len() {
+            return Err(VerifierError::InvalidFrame);
+        }
 
-    #[test]
-    fn test_default() {
-        let stack = BidirectionalStackAccount::default();
-        assert_eq!(stack.front_index, 0);
-        assert_eq!(stack.back_index, CAPACITY);
-        assert_eq!(stack.buffer, [0; CAPACITY]);
+        self.back_index
+            .checked_sub(self.front_index)
+            .ok_or(VerifierError::InvalidFrame)
     }
 
-    #[test]
-    fn test_push_front_and_borrow_front() {
-        let mut stack = BidirectionalStackAccount::default();
+    /// Byte range of the data in the front frame whose length prefix ends at `end`
+    fn front_frame_ending_at(&self, end: usize) -> Result<Range<usize>, VerifierError> {
+        // The prefix sits just below the frame end, read downwards from it
+        let (data_length, length_size) = decode_length(self.buffer[..end].iter().rev().copied())?;
+        let length_start = end - length_size;
+        let data_start = length_start
+            .checked_sub(data_length)
+            .ok_or(VerifierError::InvalidFrame)?;
 
-        // Push data to front
-        let data = [1, 2, 3, 4];
-        stack.push_front(&data).unwrap();
+        Ok(data_start..length_start)
+    }
 
-        // Borrow and verify
-        let borrowed = stack.borrow_front();
-        assert_eq!(borrowed, &[1, 2, 3, 4]);
+    /// Byte range of the data in the back frame whose length prefix starts at `start`
+    fn back_frame_starting_at(&self, start: usize) -> Result<Range<usize>, VerifierError> {
+        // The prefix sits at the frame start, read upwards from it
+        let (data_length, length_size) = decode_length(self.buffer[start..].iter().copied())?;
+        let data_start = start + length_size;
+        let data_end = data_start
+            .checked_add(data_length)
+            .filter(|data_end| *data_end <= self.buffer.len())
+            .ok_or(VerifierError::InvalidFrame)?;
+
+        Ok(data_start..data_end)
     }
 
-    #[test]
-    fn test_push_back_and_borrow_back() {
-        let mut stack = BidirectionalStackAccount::default();
+    /// Byte ranges of the front frames, from the top of the stack downwards
+    ///
+    /// The walk stops after the first error, so a corrupted frame is reported once.
+    fn front_ranges(&self) -> impl Iterator<Item = Result<Range<usize>, VerifierError>> + '_ {
+        let mut next = Some(self.free_space().map(|_| self.front_index));
+        iter::from_fn(move || match next.take()? {
+            Ok(0) => None,
+            Ok(end) => {
+                let frame = self.front_frame_ending_at(end);
+                if let Ok(frame) = &frame {
+                    next = Some(Ok(frame.start));
+                }
+                Some(frame)
+            }
+            Err(error) => Some(Err(error)),
+        })
+    }
 
-        // Push data to back
-        let data = [1, 2, 3, 4];
-        stack.push_back(&data).unwrap();
+    /// Byte ranges of the back frames, from the top of the stack downwards
+    ///
+    /// The walk stops after the first error, so a corrupted frame is reported once.
+    fn back_ranges(&self) -> impl Iterator<Item = Result<Range<usize>, VerifierError>> + '_ {
+        let mut next = Some(self.free_space().map(|_| self.back_index));
+        iter::from_fn(move || match next.take()? {
+            Ok(start) if start == self.buffer.len() => None,
+            Ok(start) => {
+                let frame = self.back_frame_starting_at(start);
+                if let Ok(frame) = &frame {
+                    next = Some(Ok(frame.end));
+                }
+                Some(frame)
+            }
+            Err(error) => Some(Err(error)),
+        })
+    }
 
-        // Borrow and verify
-        let borrowed = stack.borrow_back();
-        assert_eq!(borrowed, &[1, 2, 3, 4]);
+    /// Byte range of the data in the topmost frame on the front side
+    fn front_frame(&self) -> Result<Range<usize>, VerifierError> {
+        nth_frame(self.front_ranges(), 0)
     }
 
-    #[test]
-    fn test_push_pop_front() {
-        let mut stack = BidirectionalStackAccount::default();
+    /// Byte range of the data in the topmost frame on the back side
+    fn back_frame(&self) -> Result<Range<usize>, VerifierError> {
+        nth_frame(self.back_ranges(), 0)
+    }
+}
+
+/// Frames uploaded in chunks
+///
+/// A frame too large for one instruction is staged in the free space right above the
+/// front side, where it would land if pushed in one go, then committed as a front frame.
+/// Anything pushed to the front side before the commit overwrites the staged bytes.
+impl BidirectionalStackAccount {
+    /// Write `bytes` at `offset` into the staged frame
+    pub fn write_staged(&mut self, offset: usize, bytes: &[u8]) -> Result<(), VerifierError> {
+        let end = offset
+            .checked_add(bytes.len())
+            .ok_or(VerifierError::StackCapacity)?;
+        if end > self.free_space()? {
+            return Err(VerifierError::StackCapacity);
+        }
 
-        // Initial state
-        assert_eq!(stack.front_index, 0);
+        let start = self.front_index + offset;
+        self.buffer[start..start + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
 
-        // Push data
-        let data = [5, 6, 7, 8];
-        stack.push_front(&data).unwrap();
+    /// Borrow the first `length` bytes of the staged frame
+    pub fn staged(&self, length: usize) -> Result<&[u8], VerifierError> {
+        if length > self.free_space()? {
+            return Err(VerifierError::StackCapacity);
+        }
 
-        // Verify front_index moved
-        assert_eq!(stack.front_index, data.len() + crate::state::LENGTH_SIZE);
+        Ok(&self.buffer[self.front_index..self.front_index + length])
+    }
 
-        // Pop data
-        stack.pop_front();
+    /// Turn the first `length` staged bytes into the top front frame
+    pub fn commit_staged(&mut self, length: usize) -> Result<(), VerifierError> {
+        let (length_bytes, length_size) = encode_length(length)?;
+        if length + length_size > self.free_space()? {
+            return Err(VerifierError::StackCapacity);
+        }
 
-        // Verify front_index returned to initial state
-        assert_eq!(stack.front_index, 0);
+        let data_end = self.front_index + length;
+        for (offset, byte) in length_bytes[..length_size].iter().enumerate() {
+            self.buffer[data_end + length_size - 1 - offset] = *byte;
+        }
+        self.front_index = data_end + length_size;
+
+        Ok(())
     }
+}
 
-    #[test]
-    fn test_push_pop_back() {
-        let mut stack = BidirectionalStackAccount::default();
+/// Take the `n`-th frame range, counting from zero at the top of the stack
+fn nth_frame(
+    mut frames: impl Iterator<Item = Result<Range<usize>, VerifierError>>,
+    n: usize,
+) -> Result<Range<usize>, VerifierError> {
+    for _ in 0..n {
+        frames.next().ok_or(VerifierError::EmptyStack)??;
+    }
+    frames.next().ok_or(VerifierError::EmptyStack)?
+}
 
-        // Initial state
-        assert_eq!(stack.back_index, CAPACITY);
+impl BidirectionalStack for BidirectionalStackAccount {
+    type Error = VerifierError;
 
-        // Push data
-        let data = [5, 6, 7, 8];
-        stack.push_back(&data).unwrap();
+    fn push_front(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        // A whole frame is staged and committed at once
+        self.write_staged(0, data)?;
+        self.commit_staged(data.len())
+    }
 
-        // Verify back_index moved
-        assert_eq!(
-            stack.back_index,
-            CAPACITY - data.len() - crate::state::LENGTH_SIZE
-        );
+    fn push_back(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let (length_bytes, length_size) = encode_length(data.len())?;
+        if data.len() + length_size > self.free_space()? {
+            return Err(VerifierError::StackCapacity);
+        }
 
-        // Pop data
-        stack.pop_back();
+        let data_start = self.back_index - data.len();
+        self.buffer[data_start..self.back_index].copy_from_slice(data);
+        self.back_index = data_start - length_size;
+        self.buffer[self.back_index..data_start].copy_from_slice(&length_bytes[..length_size]);
 
-        // Verify back_index returned to initial state
-        assert_eq!(stack.back_index, CAPACITY);
+        Ok(())
     }
 
-    #[test]
-    fn test_multiple_push_front() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn pop_front(&mut self) -> Result<(), Self::Error> {
+        self.front_index = self.front_frame()?.start;
+        Ok(())
+    }
 
-        // Push first data
-        let data1 = [1, 2, 3];
-        stack.push_front(&data1).unwrap();
+    fn pop_back(&mut self) -> Result<(), Self::Error> {
+        self.back_index = self.back_frame()?.end;
+        Ok(())
+    }
 
-        // Push second data
-        let data2 = [4, 5, 6, 7];
-        stack.push_front(&data2).unwrap();
+    fn pop_front_n(&mut self, n: usize) -> Result<(), Self::Error> {
+        // Every frame down to the last one dropped is checked before the index moves
+        if let Some(last) = n.checked_sub(1) {
+            self.front_index = nth_frame(self.front_ranges(), last)?.start;
+        }
+        Ok(())
+    }
 
-        // Borrow and verify most recent data
-        let borrowed = stack.borrow_front();
-        assert_eq!(borrowed, &[4, 5, 6, 7]);
+    fn borrow_front(&self) -> Result<&[u8], Self::Error> {
+        let frame = self.front_frame()?;
+        Ok(&self.buffer[frame])
+    }
 
-        // Pop most recent data
-        stack.pop_front();
+    fn borrow_back(&self) -> Result<&[u8], Self::Error> {
+        let frame = self.back_frame()?;
+        Ok(&self.buffer[frame])
+    }
 
-        // Verify we can access the first data
-        let borrowed = stack.borrow_front();
-        assert_eq!(borrowed, &[1, 2, 3]);
+    fn borrow_front_at(&self, n: usize) -> Result<&[u8], Self::Error> {
+        let frame = nth_frame(self.front_ranges(), n)?;
+        Ok(&self.buffer[frame])
     }
 
-    #[test]
-    fn test_multiple_push_back() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn borrow_back_at(&self, n: usize) -> Result<&[u8], Self::Error> {
+        let frame = nth_frame(self.back_ranges(), n)?;
+        Ok(&self.buffer[frame])
+    }
 
-        // Push first data
-        let data1 = [1, 2, 3];
-        stack.push_back(&data1).unwrap();
+    fn front_frames(&self) -> impl Iterator<Item = Result<&[u8], Self::Error>> + '_ {
+        self.front_ranges()
+            .map(|frame| frame.map(|frame| &self.buffer[frame]))
+    }
 
-        // Push second data
-        let data2 = [4, 5, 6, 7];
-        stack.push_back(&data2).unwrap();
+    fn back_frames(&self) -> impl Iterator<Item = Result<&[u8], Self::Error>> + '_ {
+        self.back_ranges()
+            .map(|frame| frame.map(|frame| &self.buffer[frame]))
+    }
 
-        // Borrow and verify most recent data
-        let borrowed = stack.borrow_back();
-        assert_eq!(borrowed, &[4, 5, 6, 7]);
+    fn borrow_mut_front(&mut self) -> Result<&mut [u8], Self::Error> {
+        let frame = self.front_frame()?;
+        Ok(&mut self.buffer[frame])
+    }
 
-        // Pop most recent data
-        stack.pop_back();
+    fn borrow_mut_back(&mut self) -> Result<&mut [u8], Self::Error> {
+        let frame = self.back_frame()?;
+        Ok(&mut self.buffer[frame])
+    }
 
-        // Verify we can access the first data
-        let borrowed = stack.borrow_back();
-        assert_eq!(borrowed, &[1, 2, 3]);
+    fn is_empty_front(&self) -> bool {
+        self.front_index == 0
+    }
+
+    fn is_empty_back(&self) -> bool {
+        self.back_index == self.buffer.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::VerifierError,
+        frame::length_size,
+        state::{AccountHeader, BidirectionalStackAccount, JobStatus},
+    };
+    use solana_program::pubkey::Pubkey;
+    use solana_program::system_instruction::MAX_PERMITTED_DATA_LENGTH;
+    use utils::{AccountCast, BidirectionalStack, CastError};
+
+    /// Buffer sizes every test runs against, up to the largest account Solana allows
+    const CAPACITIES: [usize; 3] = [
+        4 * 1024,
+        64 * 1024,
+        MAX_PERMITTED_DATA_LENGTH as usize - BidirectionalStackAccount::HEADER_SIZE,
+    ];
+
+    fn for_each_capacity(test: impl Fn(&mut BidirectionalStackAccount)) {
+        for capacity in CAPACITIES {
+            test(&mut BidirectionalStackAccount::with_capacity(capacity));
+        }
     }
 
     #[test]
-    fn test_bidirectional_operations() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_with_capacity() {
+        for capacity in CAPACITIES {
+            let stack = BidirectionalStackAccount::with_capacity(capacity);
+            assert_eq!(stack.front_index, 0);
+            assert_eq!(stack.back_index, capacity);
+            assert_eq!(stack.capacity(), capacity);
+            assert!(stack.buffer.iter().all(|byte| *byte == 0));
+        }
+    }
 
-        // Push data to both ends
-        stack.push_front(&[1, 2, 3]).unwrap();
-        stack.push_back(&[7, 8, 9]).unwrap();
+    #[test]
+    fn test_initialize_from_account_data() {
+        for capacity in CAPACITIES {
+            let mut data = vec![0xAA; BidirectionalStackAccount::space(capacity)];
+            let stack = BidirectionalStackAccount::cast_mut(&mut data).unwrap();
+            stack.initialize();
 
-        // Verify data at both ends
-        assert_eq!(stack.borrow_front(), &[1, 2, 3]);
-        assert_eq!(stack.borrow_back(), &[7, 8, 9]);
+            assert_eq!(stack.front_index, 0);
+            assert_eq!(stack.back_index, capacity);
 
-        // Push more data to both ends
-        stack.push_front(&[4, 5, 6]).unwrap();
-        stack.push_back(&[10, 11, 12]).unwrap();
+            stack.push_front(&[1, 2, 3]).unwrap();
+            stack.push_back(&[4, 5, 6]).unwrap();
 
-        // Verify most recent data
-        assert_eq!(stack.borrow_front(), &[4, 5, 6]);
-        assert_eq!(stack.borrow_back(), &[10, 11, 12]);
+            let stack = BidirectionalStackAccount::cast(&data).unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &[1, 2, 3]);
+            assert_eq!(stack.borrow_back().unwrap(), &[4, 5, 6]);
+        }
+    }
 
-        // Pop from both ends
-        stack.pop_front();
-        stack.pop_back();
+    #[test]
+    fn test_cast_checks_length_and_alignment() {
+        let mut data = vec![0u64; 16];
+        let bytes = unsafe { std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, 128) };
+
+        assert!(matches!(
+            BidirectionalStackAccount::cast(&bytes[..BidirectionalStackAccount::HEADER_SIZE - 1]),
+            Err(CastError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            BidirectionalStackAccount::cast_mut(&mut bytes[1..]),
+            Err(CastError::Misaligned { .. })
+        ));
+        assert!(matches!(
+            AccountHeader::cast(&bytes[1..1 + AccountHeader::SIZE]),
+            Err(CastError::Misaligned { .. })
+        ));
+        assert_eq!(
+            BidirectionalStackAccount::cast(bytes).unwrap().capacity(),
+            128 - BidirectionalStackAccount::HEADER_SIZE
+        );
+    }
 
-        // Verify earlier data
-        assert_eq!(stack.borrow_front(), &[1, 2, 3]);
-        assert_eq!(stack.borrow_back(), &[7, 8, 9]);
+    #[test]
+    fn test_staged_frame_matches_push_front() {
+        for_each_capacity(|stack| {
+            let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+            stack.push_front(&[1, 2, 3]).unwrap();
+
+            // Chunks may arrive in any order and overlap
+            stack.write_staged(200, &data[200..]).unwrap();
+            stack.write_staged(0, &data[..150]).unwrap();
+            stack.write_staged(100, &data[100..250]).unwrap();
+            assert_eq!(stack.staged(data.len()).unwrap(), &data[..]);
+
+            stack.commit_staged(data.len()).unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &data[..]);
+            assert_eq!(
+                stack.front_index,
+                3 + 1 + data.len() + length_size(data.len())
+            );
+
+            stack.pop_front().unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &[1, 2, 3]);
+        });
     }
 
     #[test]
-    fn test_borrow_mut_front() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_staged_frame_must_fit() {
+        let mut stack = BidirectionalStackAccount::with_capacity(64);
+        stack.push_back(&[0; 10]).unwrap();
+        let free = 64 - 11;
+
+        assert!(stack.write_staged(free - 4, &[0; 4]).is_ok());
+        assert!(matches!(
+            stack.write_staged(free - 3, &[0; 4]),
+            Err(VerifierError::StackCapacity)
+        ));
+        assert!(matches!(
+            stack.write_staged(usize::MAX, &[0; 4]),
+            Err(VerifierError::StackCapacity)
+        ));
+
+        // The length prefix needs room as well
+        assert!(matches!(
+            stack.commit_staged(free),
+            Err(VerifierError::StackCapacity)
+        ));
+        stack.commit_staged(free - 1).unwrap();
+        assert_eq!(stack.front_index, stack.back_index);
+        assert_eq!(stack.borrow_back().unwrap(), &[0; 10]);
+    }
 
-        // Push data
-        stack.push_front(&[1, 2, 3, 4]).unwrap();
+    #[test]
+    fn test_push_front_and_borrow_front() {
+        for_each_capacity(|stack| {
+            // Push data to front
+            let data = [1, 2, 3, 4];
+            stack.push_front(&data).unwrap();
 
-        // Get mutable reference and modify
-        {
-            let data = stack.borrow_mut_front();
-            data[0] = 5;
-            data[3] = 8;
-        }
+            // Borrow and verify
+            let borrowed = stack.borrow_front().unwrap();
+            assert_eq!(borrowed, &[1, 2, 3, 4]);
+        });
+    }
 
-        // Verify modifications
-        assert_eq!(stack.borrow_front(), &[5, 2, 3, 8]);
+    #[test]
+    fn test_push_back_and_borrow_back() {
+        for_each_capacity(|stack| {
+            // Push data to back
+            let data = [1, 2, 3, 4];
+            stack.push_back(&data).unwrap();
+
+            // Borrow and verify
+            let borrowed = stack.borrow_back().unwrap();
+            assert_eq!(borrowed, &[1, 2, 3, 4]);
+        });
     }
 
     #[test]
-    fn test_borrow_mut_back() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_push_pop_front() {
+        for_each_capacity(|stack| {
+            // Initial state
+            assert_eq!(stack.front_index, 0);
+
+            // Push data
+            let data = [5, 6, 7, 8];
+            stack.push_front(&data).unwrap();
 
-        // Push data
-        stack.push_back(&[1, 2, 3, 4]).unwrap();
+            // Verify front_index moved
+            assert_eq!(stack.front_index, data.len() + length_size(data.len()));
 
-        // Get mutable reference and modify
-        {
-            let data = stack.borrow_mut_back();
-            data[0] = 5;
-            data[3] = 8;
-        }
+            // Pop data
+            stack.pop_front().unwrap();
 
-        // Verify modifications
-        assert_eq!(stack.borrow_back(), &[5, 2, 3, 8]);
+            // Verify front_index returned to initial state
+            assert_eq!(stack.front_index, 0);
+        });
     }
 
     #[test]
-    fn test_empty_data() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_push_pop_back() {
+        for_each_capacity(|stack| {
+            // Initial state
+            assert_eq!(stack.back_index, stack.capacity());
+
+            // Push data
+            let data = [5, 6, 7, 8];
+            stack.push_back(&data).unwrap();
+
+            // Verify back_index moved
+            assert_eq!(
+                stack.back_index,
+                stack.capacity() - data.len() - length_size(data.len())
+            );
+
+            // Pop data
+            stack.pop_back().unwrap();
+
+            // Verify back_index returned to initial state
+            assert_eq!(stack.back_index, stack.capacity());
+        });
+    }
+
+    #[test]
+    fn test_multiple_push_front() {
+        for_each_capacity(|stack| {
+            // Push first data
+            let data1 = [1, 2, 3];
+            stack.push_front(&data1).unwrap();
+
+            // Push second data
+            let data2 = [4, 5, 6, 7];
+            stack.push_front(&data2).unwrap();
+
+            // Borrow and verify most recent data
+            let borrowed = stack.borrow_front().unwrap();
+            assert_eq!(borrowed, &[4, 5, 6, 7]);
+
+            // Pop most recent data
+            stack.pop_front().unwrap();
+
+            // Verify we can access the first data
+            let borrowed = stack.borrow_front().unwrap();
+            assert_eq!(borrowed, &[1, 2, 3]);
+        });
+    }
+
+    #[test]
+    fn test_multiple_push_back() {
+        for_each_capacity(|stack| {
+            // Push first data
+            let data1 = [1, 2, 3];
+            stack.push_back(&data1).unwrap();
+
+            // Push second data
+            let data2 = [4, 5, 6, 7];
+            stack.push_back(&data2).unwrap();
+
+            // Borrow and verify most recent data
+            let borrowed = stack.borrow_back().unwrap();
+            assert_eq!(borrowed, &[4, 5, 6, 7]);
+
+            // Pop most recent data
+            stack.pop_back().unwrap();
+
+            // Verify we can access the first data
+            let borrowed = stack.borrow_back().unwrap();
+            assert_eq!(borrowed, &[1, 2, 3]);
+        });
+    }
 
-        // Push empty data
-        let empty: [u8; 0] = [];
-        stack.push_front(&empty).unwrap();
-        stack.push_back(&empty).unwrap();
+    #[test]
+    fn test_bidirectional_operations() {
+        for_each_capacity(|stack| {
+            // Push data to both ends
+            stack.push_front(&[1, 2, 3]).unwrap();
+            stack.push_back(&[7, 8, 9]).unwrap();
+
+            // Verify data at both ends
+            assert_eq!(stack.borrow_front().unwrap(), &[1, 2, 3]);
+            assert_eq!(stack.borrow_back().unwrap(), &[7, 8, 9]);
+
+            // Push more data to both ends
+            stack.push_front(&[4, 5, 6]).unwrap();
+            stack.push_back(&[10, 11, 12]).unwrap();
+
+            // Verify most recent data
+            assert_eq!(stack.borrow_front().unwrap(), &[4, 5, 6]);
+            assert_eq!(stack.borrow_back().unwrap(), &[10, 11, 12]);
+
+            // Pop from both ends
+            stack.pop_front().unwrap();
+            stack.pop_back().unwrap();
+
+            // Verify earlier data
+            assert_eq!(stack.borrow_front().unwrap(), &[1, 2, 3]);
+            assert_eq!(stack.borrow_back().unwrap(), &[7, 8, 9]);
+        });
+    }
 
-        // Verify empty data
-        assert_eq!(stack.borrow_front(), &[]);
-        assert_eq!(stack.borrow_back(), &[]);
+    #[test]
+    fn test_borrow_mut_front() {
+        for_each_capacity(|stack| {
+            // Push data
+            stack.push_front(&[1, 2, 3, 4]).unwrap();
+
+            // Get mutable reference and modify
+            {
+                let data = stack.borrow_mut_front().unwrap();
+                data[0] = 5;
+                data[3] = 8;
+            }
+
+            // Verify modifications
+            assert_eq!(stack.borrow_front().unwrap(), &[5, 2, 3, 8]);
+        });
+    }
 
-        // Pop empty data
-        stack.pop_front();
-        stack.pop_back();
+    #[test]
+    fn test_borrow_mut_back() {
+        for_each_capacity(|stack| {
+            // Push data
+            stack.push_back(&[1, 2, 3, 4]).unwrap();
+
+            // Get mutable reference and modify
+            {
+                let data = stack.borrow_mut_back().unwrap();
+                data[0] = 5;
+                data[3] = 8;
+            }
+
+            // Verify modifications
+            assert_eq!(stack.borrow_back().unwrap(), &[5, 2, 3, 8]);
+        });
+    }
 
-        // Verify state after popping
-        assert_eq!(stack.front_index, 0);
-        assert_eq!(stack.back_index, CAPACITY);
+    #[test]
+    fn test_empty_data() {
+        for_each_capacity(|stack| {
+            // Push empty data
+            let empty: [u8; 0] = [];
+            stack.push_front(&empty).unwrap();
+            stack.push_back(&empty).unwrap();
+
+            // Verify empty data
+            assert_eq!(stack.borrow_front().unwrap(), &[]);
+            assert_eq!(stack.borrow_back().unwrap(), &[]);
+
+            // Pop empty data
+            stack.pop_front().unwrap();
+            stack.pop_back().unwrap();
+
+            // Verify state after popping
+            assert_eq!(stack.front_index, 0);
+            assert_eq!(stack.back_index, stack.capacity());
+        });
     }
 
     #[test]
     fn test_large_data() {
-        let mut stack = BidirectionalStackAccount::default();
+        for_each_capacity(|stack| {
+            // Create larger data (1KB)
+            let large_data: Vec<u8> = (0..1024).map(|i| (i % 256) as u8).collect();
+
+            // Push to front and back
+            stack.push_front(&large_data).unwrap();
+            stack.push_back(&large_data).unwrap();
+
+            // Verify data
+            assert_eq!(stack.borrow_front().unwrap(), large_data.as_slice());
+            assert_eq!(stack.borrow_back().unwrap(), large_data.as_slice());
+        });
+    }
 
-        // Create larger data (1KB)
-        let large_data: Vec<u8> = (0..1024).map(|i| (i % 256) as u8).collect();
+    #[test]
+    fn test_alternating_operations() {
+        for_each_capacity(|stack| {
+            // Alternating push operations
+            stack.push_front(&[1, 2]).unwrap();
+            stack.push_back(&[3, 4]).unwrap();
+            stack.push_front(&[5, 6]).unwrap();
+            stack.push_back(&[7, 8]).unwrap();
+
+            // Verify most recent data
+            assert_eq!(stack.borrow_front().unwrap(), &[5, 6]);
+            assert_eq!(stack.borrow_back().unwrap(), &[7, 8]);
+
+            // Alternating pop operations
+            stack.pop_front().unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &[1, 2]);
+
+            stack.pop_back().unwrap();
+            assert_eq!(stack.borrow_back().unwrap(), &[3, 4]);
+        });
+    }
 
-        // Push to front and back
-        stack.push_front(&large_data).unwrap();
-        stack.push_back(&large_data).unwrap();
+    #[test]
+    fn test_multiple_operations_sequence() {
+        for_each_capacity(|stack| {
+            // Push multiple items
+            for i in 0..5 {
+                let data = [i, i + 1, i + 2];
+                stack.push_front(&data).unwrap();
+            }
+
+            // Verify last item
+            assert_eq!(stack.borrow_front().unwrap(), &[4, 5, 6]);
+
+            // Pop and verify each item in reverse order
+            for i in (0..5).rev() {
+                let expected = [i, i + 1, i + 2];
+                assert_eq!(stack.borrow_front().unwrap(), &expected);
+                stack.pop_front().unwrap();
+            }
+
+            // Stack should be empty now (front_index back to 0)
+            assert_eq!(stack.front_index, 0);
+        });
+    }
 
-        // Verify data
-        assert_eq!(stack.borrow_front(), large_data.as_slice());
-        assert_eq!(stack.borrow_back(), large_data.as_slice());
+    #[test]
+    fn test_mixed_data_types() {
+        for_each_capacity(|stack| {
+            // Push different types of data (converted to bytes)
+            let string_data = "Hello, world!".as_bytes();
+            let numeric_data = &[0, 1, 2, 3, 4, 5];
+            let binary_data = &[0xFF, 0xAA, 0x55, 0x00];
+
+            stack.push_front(string_data).unwrap();
+            stack.push_back(numeric_data).unwrap();
+            stack.push_front(binary_data).unwrap();
+
+            // Verify data
+            assert_eq!(stack.borrow_front().unwrap(), binary_data);
+            assert_eq!(stack.borrow_back().unwrap(), numeric_data);
+
+            // Pop and verify
+            stack.pop_front().unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), string_data);
+        });
     }
 
     #[test]
-    fn test_alternating_operations() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_boundary_conditions() {
+        for_each_capacity(|stack| {
+            // Test with single byte
+            stack.push_front(&[42]).unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &[42]);
+            stack.pop_front().unwrap();
+
+            // Test with boundary values
+            let boundary_data = [0, 255, 1, 254];
+            stack.push_front(&boundary_data).unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &boundary_data);
+        });
+    }
 
-        // Alternating push operations
-        stack.push_front(&[1, 2]).unwrap();
-        stack.push_back(&[3, 4]).unwrap();
-        stack.push_front(&[5, 6]).unwrap();
-        stack.push_back(&[7, 8]).unwrap();
+    #[test]
+    fn test_capacity_management() {
+        for_each_capacity(|stack| {
+            // Calculate how much data we can safely push (leaving some margin)
+            // This is a simple test to verify we can use a significant portion of capacity
+            let safe_capacity = stack.capacity() / 4;
+            let data = vec![1u8; safe_capacity];
 
-        // Verify most recent data
-        assert_eq!(stack.borrow_front(), &[5, 6]);
-        assert_eq!(stack.borrow_back(), &[7, 8]);
+            // We should be able to push this data both to front and back
+            stack.push_front(&data).unwrap();
+            stack.push_back(&data).unwrap();
+
+            // Verify the data
+            assert_eq!(stack.borrow_front().unwrap().len(), safe_capacity);
+            assert_eq!(stack.borrow_back().unwrap().len(), safe_capacity);
+
+            // The front_index and back_index should reflect the data size + length bytes
+            assert_eq!(
+                stack.front_index,
+                safe_capacity + length_size(safe_capacity)
+            );
+            assert_eq!(
+                stack.back_index,
+                stack.capacity() - safe_capacity - length_size(safe_capacity)
+            );
+        });
+    }
 
-        // Alternating pop operations
-        stack.pop_front();
-        assert_eq!(stack.borrow_front(), &[1, 2]);
+    #[test]
+    fn test_pop_and_borrow_empty() {
+        for_each_capacity(|stack| {
+            assert!(matches!(stack.pop_front(), Err(VerifierError::EmptyStack)));
+            assert!(matches!(stack.pop_back(), Err(VerifierError::EmptyStack)));
+            assert!(matches!(
+                stack.borrow_front(),
+                Err(VerifierError::EmptyStack)
+            ));
+            assert!(matches!(
+                stack.borrow_back(),
+                Err(VerifierError::EmptyStack)
+            ));
+            assert!(matches!(
+                stack.borrow_mut_front(),
+                Err(VerifierError::EmptyStack)
+            ));
+            assert!(matches!(
+                stack.borrow_mut_back(),
+                Err(VerifierError::EmptyStack)
+            ));
+
+            // Failed pops must leave the indices untouched
+            assert_eq!(stack.front_index, 0);
+            assert_eq!(stack.back_index, stack.capacity());
+        });
+    }
+
+    #[test]
+    fn test_pop_past_last_frame() {
+        for_each_capacity(|stack| {
+            stack.push_front(&[1, 2, 3]).unwrap();
+            stack.push_back(&[4, 5, 6]).unwrap();
+
+            stack.pop_front().unwrap();
+            stack.pop_back().unwrap();
 
-        stack.pop_back();
-        assert_eq!(stack.borrow_back(), &[3, 4]);
+            assert!(matches!(stack.pop_front(), Err(VerifierError::EmptyStack)));
+            assert!(matches!(stack.pop_back(), Err(VerifierError::EmptyStack)));
+        });
     }
 
     #[test]
-    fn test_multiple_operations_sequence() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_push_exact_fit() {
+        let mut stack = BidirectionalStackAccount::with_capacity(1024);
+
+        // Fill the whole buffer with one frame per side
+        let half = stack.capacity() / 2 - length_size(stack.capacity() / 2);
+        stack.push_front(&vec![1u8; half]).unwrap();
+        stack.push_back(&vec![2u8; half]).unwrap();
+        assert_eq!(stack.front_index, stack.back_index);
+
+        // Not even an empty frame fits anymore
+        assert!(matches!(
+            stack.push_front(&[]),
+            Err(VerifierError::StackCapacity)
+        ));
+        assert!(matches!(
+            stack.push_back(&[]),
+            Err(VerifierError::StackCapacity)
+        ));
+
+        assert_eq!(stack.borrow_front().unwrap(), vec![1u8; half].as_slice());
+        assert_eq!(stack.borrow_back().unwrap(), vec![2u8; half].as_slice());
+    }
 
-        // Push multiple items
-        for i in 0..5 {
-            let data = [i, i + 1, i + 2];
-            stack.push_front(&data).unwrap();
-        }
+    #[test]
+    fn test_push_overflow() {
+        for_each_capacity(|stack| {
+            let frame = vec![0xAB; u16::MAX as usize];
+            let mut pushed = 0;
+            while stack.push_front(&frame).is_ok() {
+                pushed += 1;
+            }
+            assert_eq!(
+                pushed,
+                stack.capacity() / (frame.len() + length_size(frame.len()))
+            );
+
+            // A failed push must not move the indices
+            let front_index = stack.front_index;
+            assert!(matches!(
+                stack.push_front(&frame),
+                Err(VerifierError::StackCapacity)
+            ));
+            assert!(matches!(
+                stack.push_back(&frame),
+                Err(VerifierError::StackCapacity)
+            ));
+            assert_eq!(stack.front_index, front_index);
+            assert_eq!(stack.back_index, stack.capacity());
+        });
+    }
+
+    #[test]
+    fn test_push_collision() {
+        let mut stack = BidirectionalStackAccount::with_capacity(64);
+
+        stack.push_front(&[1u8; 30]).unwrap();
+        stack.push_back(&[2u8; 31]).unwrap();
+
+        // Only one byte is left, which is exactly one empty frame
+        assert!(matches!(
+            stack.push_back(&[3]),
+            Err(VerifierError::StackCapacity)
+        ));
+        assert!(matches!(
+            stack.push_front(&[3]),
+            Err(VerifierError::StackCapacity)
+        ));
+        stack.push_back(&[]).unwrap();
+
+        assert_eq!(stack.borrow_front().unwrap(), &[1u8; 30]);
+        stack.pop_back().unwrap();
+        assert_eq!(stack.borrow_back().unwrap(), &[2u8; 31]);
+    }
+
+    #[test]
+    fn test_frames_larger_than_u16() {
+        let mut stack = BidirectionalStackAccount::with_capacity(4 * 1024 * 1024);
 
-        // Verify last item
-        assert_eq!(stack.borrow_front(), &[4, 5, 6]);
+        for length in [u16::MAX as usize + 1, 1024 * 1024] {
+            let frame: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+            stack.push_front(&frame).unwrap();
+            stack.push_back(&frame).unwrap();
 
-        // Pop and verify each item in reverse order
-        for i in (0..5).rev() {
-            let expected = [i, i + 1, i + 2];
-            assert_eq!(stack.borrow_front(), &expected);
-            stack.pop_front();
+            assert_eq!(stack.borrow_front().unwrap(), frame.as_slice());
+            assert_eq!(stack.borrow_back().unwrap(), frame.as_slice());
         }
 
-        // Stack should be empty now (front_index back to 0)
-        assert_eq!(stack.front_index, 0);
+        stack.pop_front().unwrap();
+        stack.pop_back().unwrap();
+        assert_eq!(stack.borrow_front().unwrap().len(), u16::MAX as usize + 1);
+        assert_eq!(stack.borrow_back().unwrap().len(), u16::MAX as usize + 1);
     }
 
     #[test]
-    fn test_mixed_data_types() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_small_frames_use_single_byte_prefix() {
+        for_each_capacity(|stack| {
+            stack.push_front(&[7u8; 127]).unwrap();
+            stack.push_back(&[7u8; 127]).unwrap();
+            assert_eq!(stack.front_index, 128);
+            assert_eq!(stack.back_index, stack.capacity() - 128);
+
+            stack.push_front(&[8u8; 128]).unwrap();
+            stack.push_back(&[8u8; 128]).unwrap();
+            assert_eq!(stack.front_index, 128 + 130);
+            assert_eq!(stack.back_index, stack.capacity() - 128 - 130);
+
+            stack.pop_front().unwrap();
+            stack.pop_back().unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &[7u8; 127]);
+            assert_eq!(stack.borrow_back().unwrap(), &[7u8; 127]);
+        });
+    }
 
-        // Push different types of data (converted to bytes)
-        let string_data = "Hello, world!".as_bytes();
-        let numeric_data = &[0, 1, 2, 3, 4, 5];
-        let binary_data = &[0xFF, 0xAA, 0x55, 0x00];
+    #[test]
+    fn test_corrupted_frame_length() {
+        let mut stack = BidirectionalStackAccount::with_capacity(1024);
+        stack.push_front(&[1, 2, 3]).unwrap();
+        stack.push_back(&[4, 5, 6]).unwrap();
+
+        // Length prefixes claiming more data than the side holds
+        let front_length = stack.front_index - 1;
+        stack.buffer[front_length] = 100;
+        let back_length = stack.back_index;
+        stack.buffer[back_length] = 0xFF;
+
+        assert!(matches!(
+            stack.borrow_front(),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(
+            stack.pop_front(),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(
+            stack.borrow_back(),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(stack.pop_back(), Err(VerifierError::InvalidFrame)));
+    }
 
-        stack.push_front(string_data).unwrap();
-        stack.push_back(numeric_data).unwrap();
-        stack.push_front(binary_data).unwrap();
+    #[test]
+    fn test_corrupted_indices() {
+        let mut stack = BidirectionalStackAccount::with_capacity(1024);
+        stack.push_front(&[1, 2, 3]).unwrap();
 
-        // Verify data
-        assert_eq!(stack.borrow_front(), binary_data);
-        assert_eq!(stack.borrow_back(), numeric_data);
+        // Front and back regions overlapping
+        stack.back_index = 1;
+        assert!(matches!(
+            stack.push_front(&[]),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(
+            stack.borrow_front(),
+            Err(VerifierError::InvalidFrame)
+        ));
+
+        // Back index past the end of the buffer
+        stack.back_index = stack.capacity() + 1;
+        assert!(matches!(
+            stack.push_back(&[]),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(stack.pop_back(), Err(VerifierError::InvalidFrame)));
+    }
 
-        // Pop and verify
-        stack.pop_front();
-        assert_eq!(stack.borrow_front(), string_data);
+    #[test]
+    fn test_borrow_at() {
+        for_each_capacity(|stack| {
+            for i in 0..5u8 {
+                stack.push_front(&[i; 3]).unwrap();
+                stack.push_back(&[i; 200]).unwrap();
+            }
+
+            for n in 0..5 {
+                let i = 4 - n as u8;
+                assert_eq!(stack.borrow_front_at(n).unwrap(), &[i; 3]);
+                assert_eq!(stack.borrow_back_at(n).unwrap(), &[i; 200]);
+            }
+            assert_eq!(
+                stack.borrow_front_at(0).unwrap(),
+                stack.borrow_front().unwrap()
+            );
+            assert_eq!(
+                stack.borrow_back_at(0).unwrap(),
+                stack.borrow_back().unwrap()
+            );
+
+            assert!(matches!(
+                stack.borrow_front_at(5),
+                Err(VerifierError::EmptyStack)
+            ));
+            assert!(matches!(
+                stack.borrow_back_at(5),
+                Err(VerifierError::EmptyStack)
+            ));
+        });
     }
 
     #[test]
-    fn test_boundary_conditions() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_frame_iterators() {
+        for_each_capacity(|stack| {
+            assert_eq!(stack.front_frames().count(), 0);
+            assert_eq!(stack.back_frames().count(), 0);
+
+            stack.push_front(&[1]).unwrap();
+            stack.push_front(&[]).unwrap();
+            stack.push_front(&[2, 3]).unwrap();
+            stack.push_back(&[4, 5, 6]).unwrap();
+            stack.push_back(&[7]).unwrap();
+
+            let front: Vec<&[u8]> = stack.front_frames().map(Result::unwrap).collect();
+            assert_eq!(front, [&[2, 3][..], &[], &[1]]);
+            let back: Vec<&[u8]> = stack.back_frames().map(Result::unwrap).collect();
+            assert_eq!(back, [&[7][..], &[4, 5, 6]]);
+        });
+    }
+
+    #[test]
+    fn test_frame_iterator_stops_at_corrupted_frame() {
+        let mut stack = BidirectionalStackAccount::with_capacity(1024);
+        stack.push_front(&[1, 2, 3]).unwrap();
+        stack.push_front(&[4, 5, 6]).unwrap();
 
-        // Test with single byte
-        stack.push_front(&[42]).unwrap();
-        assert_eq!(stack.borrow_front(), &[42]);
-        stack.pop_front();
+        // The lower frame claims more data than there is below it
+        stack.buffer[3] = 100;
 
-        // Test with boundary values
-        let boundary_data = [0, 255, 1, 254];
-        stack.push_front(&boundary_data).unwrap();
-        assert_eq!(stack.borrow_front(), &boundary_data);
+        {
+            let mut frames = stack.front_frames();
+            assert_eq!(frames.next().unwrap().unwrap(), &[4, 5, 6]);
+            assert!(matches!(
+                frames.next(),
+                Some(Err(VerifierError::InvalidFrame))
+            ));
+            assert!(frames.next().is_none());
+        }
+
+        assert!(matches!(
+            stack.borrow_front_at(1),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert!(matches!(
+            stack.pop_front_n(2),
+            Err(VerifierError::InvalidFrame)
+        ));
+        assert_eq!(stack.front_index, 8);
     }
 
     #[test]
-    fn test_capacity_management() {
-        let mut stack = BidirectionalStackAccount::default();
+    fn test_pop_front_n() {
+        for_each_capacity(|stack| {
+            for i in 0..5u8 {
+                stack.push_front(&[i; 2]).unwrap();
+            }
+
+            stack.pop_front_n(0).unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &[4; 2]);
+
+            stack.pop_front_n(3).unwrap();
+            assert_eq!(stack.borrow_front().unwrap(), &[1; 2]);
+
+            // Popping more frames than there are leaves the stack untouched
+            let front_index = stack.front_index;
+            assert!(matches!(
+                stack.pop_front_n(3),
+                Err(VerifierError::EmptyStack)
+            ));
+            assert_eq!(stack.front_index, front_index);
+
+            stack.pop_front_n(2).unwrap();
+            assert!(stack.is_empty_front());
+        });
+    }
 
-        // Calculate how much data we can safely push (leaving some margin)
-        // This is a simple test to verify we can use a significant portion of capacity
-        let safe_capacity = CAPACITY / 4; // Using 1/4 of capacity for safety
-        let data = vec![1u8; safe_capacity];
+    #[test]
+    fn test_header_initialize() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![0u8; AccountHeader::account_space(1024)];
+        let (header, stack) = AccountHeader::split_mut(&mut data).unwrap();
+
+        assert!(matches!(
+            header.validate(),
+            Err(VerifierError::NotInitialized)
+        ));
+        header.initialize(authority).unwrap();
+        stack.initialize();
+
+        let (header, stack) = AccountHeader::split(&data).unwrap();
+        header.validate().unwrap();
+        assert_eq!(header.magic, AccountHeader::MAGIC);
+        assert_eq!(header.version, AccountHeader::VERSION);
+        assert_eq!(header.authority, authority);
+        assert_eq!(header.status().unwrap(), JobStatus::Created);
+        assert_eq!(header.steps, 0);
+        assert_eq!(stack.capacity(), 1024);
+    }
 
-        // We should be able to push this data both to front and back
-        stack.push_front(&data).unwrap();
-        stack.push_back(&data).unwrap();
+    #[test]
+    fn test_header_refuses_reinitialization() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![0u8; AccountHeader::account_space(1024)];
+        let (header, stack) = AccountHeader::split_mut(&mut data).unwrap();
+        header.initialize(authority).unwrap();
+        stack.initialize();
+        stack.push_front(&[1, 2, 3]).unwrap();
+        header.set_status(JobStatus::Running);
+
+        let (header, stack) = AccountHeader::split_mut(&mut data).unwrap();
+        assert!(matches!(
+            header.initialize(Pubkey::new_unique()),
+            Err(VerifierError::AlreadyInitialized)
+        ));
+        assert_eq!(header.authority, authority);
+        assert_eq!(header.status().unwrap(), JobStatus::Running);
+        assert_eq!(stack.borrow_front().unwrap(), &[1, 2, 3]);
+    }
 
-        // Verify the data
-        assert_eq!(stack.borrow_front().len(), safe_capacity);
-        assert_eq!(stack.borrow_back().len(), safe_capacity);
+    #[test]
+    fn test_header_validation() {
+        let mut data = vec![0u8; AccountHeader::account_space(1024)];
+        let (header, _) = AccountHeader::split_mut(&mut data).unwrap();
+        header.initialize(Pubkey::new_unique()).unwrap();
+
+        let mut corrupted = *header;
+        corrupted.magic = *b"NOTSTACK";
+        assert!(matches!(
+            corrupted.validate(),
+            Err(VerifierError::InvalidMagic)
+        ));
+
+        let mut corrupted = *header;
+        corrupted.version = AccountHeader::VERSION + 1;
+        assert!(matches!(
+            corrupted.validate(),
+            Err(VerifierError::UnsupportedVersion(version)) if version == AccountHeader::VERSION + 1
+        ));
+
+        let mut corrupted = *header;
+        corrupted.status = 0;
+        assert!(matches!(
+            corrupted.validate(),
+            Err(VerifierError::InvalidStatus(0))
+        ));
+    }
 
-        // The front_index and back_index should reflect the data size + length bytes
-        assert_eq!(stack.front_index, safe_capacity + crate::state::LENGTH_SIZE);
-        assert_eq!(
-            stack.back_index,
-            CAPACITY - safe_capacity - crate::state::LENGTH_SIZE
-        );
+    #[test]
+    fn test_header_split_too_small() {
+        let mut data = vec![0u8; AccountHeader::account_space(0) - 1];
+        assert!(matches!(
+            AccountHeader::split(&data),
+            Err(VerifierError::AccountTooSmall)
+        ));
+        assert!(matches!(
+            AccountHeader::split_mut(&mut data),
+            Err(VerifierError::AccountTooSmall)
+        ));
+
+        let data = vec![0u8; AccountHeader::account_space(0)];
+        let (_, stack) = AccountHeader::split(&data).unwrap();
+        assert_eq!(stack.capacity(), 0);
     }
 }
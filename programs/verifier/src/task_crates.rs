@@ -0,0 +1,18 @@
+/// Expand `$apply!` with every crate the verifier executes tasks from
+///
+/// Each crate exports the `TASKS` list and `dispatch` function of
+/// `utils::register_tasks!`. This is the only list of them: the scheduler and the
+/// build script both `include!` this file, so the dispatcher and the task manifest
+/// always cover the same crates.
+macro_rules! task_crates {
+    ($apply:ident) => {
+        $apply! { arithmetic, stark }
+    };
+}
+
+/// Task lists of the given crates, in dispatch order
+macro_rules! task_lists {
+    ($($krate:ident),+) => {
+        &[$($krate::TASKS),+]
+    };
+}
@@ -2,58 +2,56 @@ use arithmetic::*;
 use utils::{BidirectionalStack, Scheduler};
 use verifier::state::BidirectionalStackAccount;
 
+const CAPACITY: usize = 64 * 1024;
+
 #[test]
 fn test_add_operation() {
-    let mut stack = BidirectionalStackAccount::default();
-    stack.push_task(add::Add::new(48, 52));
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_task(add::Add::new(48, 52)).unwrap();
 
     while !stack.is_empty_back() {
-        stack.execute();
+        stack.execute().unwrap();
     }
 
-    let result = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
+    let result: u128 = stack.pop_value().unwrap();
     assert_eq!(result, 100);
-    stack.pop_front();
 }
 
 #[test]
 fn test_multiply_operation() {
-    let mut stack = BidirectionalStackAccount::default();
-    stack.push_task(mul::Mul::new(5, 7));
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_task(mul::Mul::new(5, 7)).unwrap();
 
     while !stack.is_empty_back() {
-        stack.execute();
+        stack.execute().unwrap();
     }
 
-    let result = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
+    let result: u128 = stack.pop_value().unwrap();
     assert_eq!(result, 35);
-    stack.pop_front();
 }
 
 #[test]
 fn test_exponentiation_operation() {
-    let mut stack = BidirectionalStackAccount::default();
-    stack.push_task(exp::Exp::new(2, 10));
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_task(exp::Exp::new(2, 10)).unwrap();
 
     while !stack.is_empty_back() {
-        stack.execute();
+        stack.execute().unwrap();
     }
 
-    let result = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
+    let result: u128 = stack.pop_value().unwrap();
     assert_eq!(result, 1024);
-    stack.pop_front();
 }
 
 #[test]
 fn test_fibonacci_operation() {
-    let mut stack = BidirectionalStackAccount::default();
-    stack.push_task(fib::Fibonacci::new(19));
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_task(fib::Fibonacci::new(19)).unwrap();
 
     while !stack.is_empty_back() {
-        stack.execute();
+        stack.execute().unwrap();
     }
 
-    let result = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
+    let result: u128 = stack.pop_value().unwrap();
     assert_eq!(result, 4181);
-    stack.pop_front();
 }
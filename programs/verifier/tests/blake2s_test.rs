@@ -0,0 +1,149 @@
+mod common;
+
+use blake2::{Blake2s256, Digest};
+use common::{assert_invalid_bit_pattern, from_hex, pop_result, run, CAPACITY};
+use stark::blake2s::{Blake2s, Blake2sOutput};
+use stark::felt::Felt;
+use utils::{BidirectionalStack, Executable, Scheduler, TaskError};
+use verifier::{error::VerifierError, state::BidirectionalStackAccount};
+
+fn blake2s_bytes(bytes: &[u8], output: Blake2sOutput) -> Vec<u8> {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    Blake2s::push_bytes(bytes, stack.as_mut()).unwrap();
+    stack.push_task(Blake2s::new_bytes(bytes, output)).unwrap();
+
+    let steps = run(&mut stack);
+    assert_eq!(steps, bytes.len().div_ceil(Blake2s::CHUNK_BYTES).max(1));
+
+    pop_result(&mut stack)
+}
+
+fn blake2s_felts(felts: &[Felt], output: Blake2sOutput) -> Vec<u8> {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    Blake2s::push_input(felts, stack.as_mut()).unwrap();
+    stack.push_task(Blake2s::new(felts, output)).unwrap();
+    run(&mut stack);
+
+    pop_result(&mut stack)
+}
+
+#[test]
+fn test_blake2s_vectors() {
+    assert_eq!(
+        blake2s_bytes(b"", Blake2sOutput::Full),
+        from_hex("69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9")
+    );
+    // RFC 7693, appendix B
+    assert_eq!(
+        blake2s_bytes(b"abc", Blake2sOutput::Full),
+        from_hex("508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982")
+    );
+}
+
+#[test]
+fn test_blake2s_long_inputs() {
+    // Lengths around block and chunk boundaries
+    let chunk = Blake2s::CHUNK_BYTES;
+    for length in [
+        1,
+        63,
+        64,
+        65,
+        128,
+        chunk - 1,
+        chunk,
+        chunk + 1,
+        3 * chunk + 100,
+    ] {
+        let bytes: Vec<u8> = (0..length).map(|i| (i * 7 + 3) as u8).collect();
+        assert_eq!(
+            blake2s_bytes(&bytes, Blake2sOutput::Full),
+            Blake2s256::digest(&bytes).to_vec(),
+            "length {length}"
+        );
+    }
+}
+
+#[test]
+fn test_blake2s_felts() {
+    // Felts are hashed as their big-endian encodings
+    let felts: Vec<Felt> = (0..40u64)
+        .map(|i| Felt::from(i) - Felt::from(7u64))
+        .collect();
+    let encoded: Vec<u8> = felts.iter().flat_map(|f| f.to_bytes_be()).collect();
+    assert_eq!(
+        blake2s_felts(&felts, Blake2sOutput::Full),
+        Blake2s256::digest(&encoded).to_vec()
+    );
+}
+
+#[test]
+fn test_blake2s_commitment_node() {
+    // Merkle nodes hash the concatenated children, truncated like the leaves: the digest
+    // read as a big-endian integer keeps its 160 or 248 least significant bits. Expected
+    // digests computed with Python's `hashlib.blake2s`.
+    let vectors = [
+        (
+            Blake2sOutput::Masked160Lsb,
+            "0000000000000000000000000102030405060708090a0b0c0d0e0f1011121314",
+            "00000000000000000000000015161718191a1b1c1d1e1f202122232425262728",
+            "00000000000000000000000097136a5215c3a69305e2f16f5bf606e25baee92f",
+        ),
+        (
+            Blake2sOutput::Masked248Lsb,
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "00202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e",
+            "00a786af3bd6fb8503502a34bd69f3c56d92600f31bec1d546c4578b8da1b5cc",
+        ),
+    ];
+    for (output, left, right, expected) in vectors {
+        let children = [from_hex(left), from_hex(right)].concat();
+        assert_eq!(
+            blake2s_bytes(&children, output),
+            from_hex(expected),
+            "{output:?}"
+        );
+    }
+}
+
+#[test]
+fn test_blake2s_truncated_outputs() {
+    let full = blake2s_bytes(b"abc", Blake2sOutput::Full);
+
+    let lsb = blake2s_bytes(b"abc", Blake2sOutput::Masked160Lsb);
+    assert_eq!(lsb[..12], [0; 12]);
+    assert_eq!(lsb[12..], full[12..]);
+
+    let msb = blake2s_bytes(b"abc", Blake2sOutput::Masked160Msb);
+    assert_eq!(msb[..20], full[..20]);
+    assert_eq!(msb[20..], [0; 12]);
+
+    let felt = blake2s_bytes(b"abc", Blake2sOutput::Masked248Lsb);
+    assert_eq!(felt[0], 0);
+    assert_eq!(felt[1..], full[1..]);
+}
+
+#[test]
+fn test_blake2s_with_invalid_output() {
+    let mut task = Blake2s::new_bytes(b"", Blake2sOutput::Full).to_vec_with_type_tag();
+    // The output mode follows the type tag, the state, the length and the counter
+    task[4 + 32 + 16] = 4;
+
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_value(&Vec::<u8>::new()).unwrap();
+    assert_invalid_bit_pattern(&mut stack, &task);
+}
+
+#[test]
+fn test_blake2s_rejects_mismatched_chunks() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_value(&b"abcd".to_vec()).unwrap();
+    stack
+        .push_task(Blake2s::new_bytes(b"abc", Blake2sOutput::Full))
+        .unwrap();
+
+    assert!(matches!(
+        stack.execute(),
+        Err(VerifierError::Task(TaskError::InvalidState(_)))
+    ));
+}
@@ -0,0 +1,48 @@
+//! Fixtures shared by the task tests
+#![allow(dead_code)]
+
+use stark::felt::Felt;
+use utils::{BidirectionalStack, CastError, StackValue};
+use verifier::{error::VerifierError, state::BidirectionalStackAccount};
+
+pub const CAPACITY: usize = 64 * 1024;
+
+pub fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// The same value in the reference implementation
+pub fn reference(felt: Felt) -> starknet_types_core::felt::Felt {
+    starknet_types_core::felt::Felt::from_bytes_be(&felt.to_bytes_be())
+}
+
+/// Run every task on the stack and return the number of steps taken
+pub fn run(stack: &mut BidirectionalStackAccount) -> usize {
+    let mut steps = 0;
+    while !stack.is_empty_back() {
+        stack.execute().unwrap();
+        steps += 1;
+    }
+    steps
+}
+
+/// Pop the result of a finished job, which must be the last value on the stack
+pub fn pop_result<V: StackValue>(stack: &mut BidirectionalStackAccount) -> V {
+    let result = stack.pop_value().unwrap();
+    assert_eq!(stack.front_index, 0, "Stack should be empty after test");
+    result
+}
+
+/// Push a task frame with a corrupted enum field and check that it is rejected untouched
+pub fn assert_invalid_bit_pattern(stack: &mut BidirectionalStackAccount, task: &[u8]) {
+    stack.push_back(task).unwrap();
+
+    assert!(matches!(
+        stack.execute(),
+        Err(VerifierError::Cast(CastError::InvalidBitPattern { .. }))
+    ));
+    assert_eq!(stack.borrow_back().unwrap(), task);
+}
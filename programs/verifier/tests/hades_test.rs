@@ -1,7 +1,10 @@
+mod common;
+
+use common::{assert_invalid_bit_pattern, CAPACITY};
 use stark::felt::Felt;
 use stark::poseidon::hades::HadesPermutation;
-use utils::{BidirectionalStack, Scheduler};
-use verifier::state::BidirectionalStackAccount;
+use utils::{BidirectionalStack, CastError, Executable, Scheduler, TaskError};
+use verifier::{error::VerifierError, state::BidirectionalStackAccount};
 
 #[test]
 fn test_hades_permutation() {
@@ -13,26 +16,18 @@ fn test_hades_permutation() {
     ];
 
     // Create a stack and push the Hades permutation task
-    let mut stack = BidirectionalStackAccount::default();
-    stack.push_task(HadesPermutation::new(state));
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_task(HadesPermutation::new(state)).unwrap();
 
     // Execute until completion
     let mut steps = 0;
     while !stack.is_empty_back() {
-        stack.execute();
+        stack.execute().unwrap();
         steps += 1;
     }
 
     // Get the result from the stack
-    let bytes = stack.borrow_front();
-    let result1 = Felt::from_bytes_be_slice(bytes);
-    stack.pop_front();
-    let bytes = stack.borrow_front();
-    let result2 = Felt::from_bytes_be_slice(bytes);
-    stack.pop_front();
-    let bytes = stack.borrow_front();
-    let result3 = Felt::from_bytes_be_slice(bytes);
-    stack.pop_front();
+    let [result1, result2, result3]: [Felt; 3] = stack.pop_value().unwrap();
 
     // The expected output should match the result we got
     let expected_result1 =
@@ -51,3 +46,74 @@ fn test_hades_permutation() {
     assert!(steps > 0, "Should have executed at least one step");
     assert_eq!(stack.front_index, 0, "Stack should be empty after test");
 }
+
+#[test]
+fn test_hades_permutation_queued_under_legacy_tag() {
+    let state = [Felt::from(9u64), Felt::from(11u64), Felt::from(2u64)];
+
+    // A frame pushed before the task had a stable identifier
+    let legacy_tag = utils::fnv1a(b"stark::poseidon::hades::HadesPermutation");
+    let task = HadesPermutation::new(state);
+    let frame = [&legacy_tag.to_be_bytes()[..], task.as_bytes()].concat();
+
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_back(&frame).unwrap();
+
+    // The first step rewrites the frame under the current tag
+    stack.execute().unwrap();
+    let current = stack.borrow_back().unwrap();
+    assert_eq!(&current[..4], &HadesPermutation::TYPE_TAG.to_be_bytes());
+    assert_eq!(current.len(), frame.len());
+
+    while !stack.is_empty_back() {
+        stack.execute().unwrap();
+    }
+    let legacy_result: [Felt; 3] = stack.pop_value().unwrap();
+
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_task(HadesPermutation::new(state)).unwrap();
+    while !stack.is_empty_back() {
+        stack.execute().unwrap();
+    }
+    assert_eq!(legacy_result, stack.pop_value::<[Felt; 3]>().unwrap());
+}
+
+#[test]
+fn test_hades_permutation_with_invalid_phase() {
+    let mut task = HadesPermutation::new([Felt::ZERO; 3]).to_vec_with_type_tag();
+    // The phase follows the type tag and the three state elements
+    task[4 + 3 * 32] = 3;
+
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    assert_invalid_bit_pattern(&mut stack, &task);
+}
+
+#[test]
+fn test_hades_permutation_with_invalid_length() {
+    let task = HadesPermutation::new([Felt::ZERO; 3]).to_vec_with_type_tag();
+
+    for task in [&task[..task.len() - 1], &[&task[..], &[0]].concat()] {
+        let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+        stack.push_back(task).unwrap();
+
+        assert!(matches!(
+            stack.execute(),
+            Err(VerifierError::Cast(CastError::InvalidLength { .. }))
+        ));
+    }
+}
+
+#[test]
+fn test_hades_permutation_with_invalid_constants_index() {
+    let mut task = HadesPermutation::new([Felt::ZERO; 3]).to_vec_with_type_tag();
+    // The constants index follows the phase
+    task[4 + 3 * 32 + 8] = 200;
+
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_back(&task).unwrap();
+
+    assert!(matches!(
+        stack.execute(),
+        Err(VerifierError::Task(TaskError::InvalidState(_)))
+    ));
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{assert_invalid_bit_pattern, from_hex, pop_result, CAPACITY};
+use stark::felt::Felt;
+use stark::keccak::{KeccakBytes, KeccakFelts, KeccakOutput};
+use utils::{BidirectionalStack, Executable, Scheduler};
+use verifier::{error::VerifierError, state::BidirectionalStackAccount};
+
+fn keccak_bytes(frames: &[Vec<u8>], output: KeccakOutput) -> Vec<u8> {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    KeccakBytes::push_input(frames, stack.as_mut()).unwrap();
+    stack.push_task(KeccakBytes::new(frames, output)).unwrap();
+    stack.execute().unwrap();
+    assert!(stack.is_empty_back());
+
+    pop_result(&mut stack)
+}
+
+fn keccak_felts(felts: &[Felt], output: KeccakOutput) -> Vec<u8> {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    KeccakFelts::push_input(felts, stack.as_mut()).unwrap();
+    stack.push_task(KeccakFelts::new(felts, output)).unwrap();
+    stack.execute().unwrap();
+    assert!(stack.is_empty_back());
+
+    pop_result(&mut stack)
+}
+
+#[test]
+fn test_keccak_bytes() {
+    assert_eq!(
+        keccak_bytes(&[vec![]], KeccakOutput::Full),
+        from_hex("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+    );
+    assert_eq!(
+        keccak_bytes(&[b"abc".to_vec()], KeccakOutput::Full),
+        from_hex("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+    );
+}
+
+#[test]
+fn test_keccak_bytes_hashes_frames_in_order() {
+    // Frames are concatenated, the first one pushed as input hashed first
+    let split = keccak_bytes(&[b"a".to_vec(), b"bc".to_vec()], KeccakOutput::Full);
+    assert_eq!(split, keccak_bytes(&[b"abc".to_vec()], KeccakOutput::Full));
+    assert_ne!(
+        split,
+        keccak_bytes(&[b"bc".to_vec(), b"a".to_vec()], KeccakOutput::Full)
+    );
+}
+
+#[test]
+fn test_keccak_felts() {
+    assert_eq!(
+        keccak_felts(&[Felt::ZERO], KeccakOutput::Full),
+        from_hex("290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563")
+    );
+
+    // Felts are hashed as their big-endian encodings
+    let felts = [Felt::from(3u64), Felt::ZERO - Felt::ONE];
+    let encoded: Vec<Vec<u8>> = felts.iter().map(|f| f.to_bytes_be().to_vec()).collect();
+    assert_eq!(
+        keccak_felts(&felts, KeccakOutput::Full),
+        keccak_bytes(&encoded, KeccakOutput::Full)
+    );
+}
+
+#[test]
+fn test_keccak_commitment_node() {
+    // Merkle nodes hash the concatenated children, truncated like the leaves: the digest
+    // read as a big-endian integer keeps its 160 least or most significant bits.
+    // Expected digests computed with a Python Keccak-256 checked against `hashlib.sha3_256`.
+    let vectors = [
+        (
+            KeccakOutput::Masked160Lsb,
+            "0000000000000000000000000102030405060708090a0b0c0d0e0f1011121314",
+            "00000000000000000000000015161718191a1b1c1d1e1f202122232425262728",
+            "0000000000000000000000000516abda493342e9eb0ce0a5482434f35b6fc8a8",
+        ),
+        (
+            KeccakOutput::Masked160Msb,
+            "0102030405060708090a0b0c0d0e0f1011121314000000000000000000000000",
+            "15161718191a1b1c1d1e1f202122232425262728000000000000000000000000",
+            "459a5130af6612f975184ca863334111a8370061000000000000000000000000",
+        ),
+    ];
+    for (output, left, right, expected) in vectors {
+        let children = [from_hex(left), from_hex(right)];
+        assert_eq!(
+            keccak_bytes(&children, output),
+            from_hex(expected),
+            "{output:?}"
+        );
+    }
+}
+
+#[test]
+fn test_keccak_masked_outputs() {
+    let input = [b"abc".to_vec()];
+    let full = keccak_bytes(&input, KeccakOutput::Full);
+
+    let lsb = keccak_bytes(&input, KeccakOutput::Masked160Lsb);
+    assert_eq!(lsb[..12], [0; 12]);
+    assert_eq!(lsb[12..], full[12..]);
+
+    let msb = keccak_bytes(&input, KeccakOutput::Masked160Msb);
+    assert_eq!(msb[..20], full[..20]);
+    assert_eq!(msb[20..], [0; 12]);
+
+    let felts = [Felt::ONE, Felt::TWO];
+    let full = keccak_felts(&felts, KeccakOutput::Full);
+    assert_eq!(
+        keccak_felts(&felts, KeccakOutput::Masked160Lsb)[12..],
+        full[12..]
+    );
+}
+
+#[test]
+fn test_keccak_with_invalid_output() {
+    let mut task = KeccakBytes::new(&[vec![]], KeccakOutput::Full).to_vec_with_type_tag();
+    // The output mode follows the type tag and the frame count
+    task[4 + 8] = 3;
+
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_value(&Vec::<u8>::new()).unwrap();
+    assert_invalid_bit_pattern(&mut stack, &task);
+}
+
+#[test]
+fn test_keccak_felts_rejects_byte_frames() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_value(&vec![0u8; 32]).unwrap();
+    stack
+        .push_task(KeccakFelts::new(&[Felt::ZERO], KeccakOutput::Full))
+        .unwrap();
+
+    assert!(matches!(stack.execute(), Err(VerifierError::Value(_))));
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{assert_invalid_bit_pattern, pop_result, reference, run, CAPACITY};
+use stark::felt::Felt;
+use stark::pedersen::{hash::PedersenHash, PedersenHashOnElements};
+use starknet_types_core::hash::{Pedersen, StarkHash};
+use utils::{BidirectionalStack, Executable, Scheduler, TaskError};
+use verifier::{error::VerifierError, state::BidirectionalStackAccount};
+
+fn pedersen_hash(a: Felt, b: Felt) -> Felt {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    PedersenHash::push_input(a, b, stack.as_mut()).unwrap();
+    stack.push_task(PedersenHash::new()).unwrap();
+
+    let steps = run(&mut stack);
+    assert_eq!(
+        steps,
+        2 * PedersenHash::N_INPUT_BITS / PedersenHash::BITS_PER_STEP
+    );
+
+    pop_result(&mut stack)
+}
+
+#[test]
+fn test_pedersen_hash() {
+    // Test vector from starkware-libs/crypto-cpp
+    let a = Felt::from_hex("0x3d937c035c878245caf64531a5756109c53068da139362728feb561405371cb")
+        .unwrap();
+    let b = Felt::from_hex("0x208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a")
+        .unwrap();
+    let expected =
+        Felt::from_hex("0x30e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662")
+            .unwrap();
+
+    assert_eq!(pedersen_hash(a, b), expected);
+}
+
+#[test]
+fn test_pedersen_hash_matches_reference() {
+    let max = Felt::ZERO - Felt::ONE;
+    let inputs = [
+        (Felt::ZERO, Felt::ZERO),
+        (Felt::ONE, Felt::ZERO),
+        (Felt::ZERO, Felt::ONE),
+        (Felt::from(0xdead_beef_u64), Felt::from(u128::MAX)),
+        // Values with the high bits set
+        (max, max),
+        (Felt::from(2u64).pow(250u128), max),
+    ];
+
+    for (a, b) in inputs {
+        assert_eq!(
+            reference(pedersen_hash(a, b)),
+            Pedersen::hash(&reference(a), &reference(b)),
+            "hash of {} and {}",
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn test_pedersen_hash_on_elements_matches_reference() {
+    for length in [0u64, 1, 2, 5] {
+        let elements: Vec<Felt> = (0..length).map(|i| Felt::from(i * 7 + 3)).collect();
+
+        let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+        PedersenHashOnElements::push_input(&elements, stack.as_mut()).unwrap();
+        stack
+            .push_task(PedersenHashOnElements::new(&elements))
+            .unwrap();
+        run(&mut stack);
+
+        let result: Felt = pop_result(&mut stack);
+        let expected =
+            Pedersen::hash_array(&elements.iter().copied().map(reference).collect::<Vec<_>>());
+        assert_eq!(reference(result), expected, "hash of {} elements", length);
+    }
+}
+
+#[test]
+fn test_pedersen_hash_with_invalid_phase() {
+    let mut task = PedersenHash::new().to_vec_with_type_tag();
+    // The phase follows the type tag, the inputs and both points
+    task[4 + 8 * 32] = 3;
+
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    assert_invalid_bit_pattern(&mut stack, &task);
+}
+
+#[test]
+fn test_pedersen_hash_with_point_off_the_curve() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    PedersenHash::push_input(Felt::ONE, Felt::ONE, stack.as_mut()).unwrap();
+    stack.push_task(PedersenHash::new()).unwrap();
+    stack.execute().unwrap();
+
+    // The accumulator follows the type tag and the inputs
+    let mut task = stack.borrow_back().unwrap().to_vec();
+    task[4 + 2 * 32] ^= 1;
+    stack.pop_back().unwrap();
+    stack.push_back(&task).unwrap();
+
+    assert!(matches!(
+        stack.execute(),
+        Err(VerifierError::Task(TaskError::InvalidState(_)))
+    ));
+}
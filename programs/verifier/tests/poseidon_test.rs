@@ -1,6 +1,10 @@
+mod common;
+
+use common::{pop_result, reference, run, CAPACITY};
 use stark::felt::Felt;
-use stark::poseidon::PoseidonHashMany;
-use utils::{BidirectionalStack, Scheduler};
+use stark::poseidon::{PoseidonHash, PoseidonHashMany, PoseidonHashSingle};
+use starknet_types_core::hash::{Poseidon, StarkHash};
+use utils::Scheduler;
 use verifier::state::BidirectionalStackAccount;
 
 #[test]
@@ -51,29 +55,87 @@ fn test_poseidon_hash_4() {
 
 fn test_hash_with_inputs(inputs: &[Felt], expected: Felt) {
     // Create a stack and push the PoseidonHashMany task
-    let mut stack = BidirectionalStackAccount::default();
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
 
     // Create the PoseidonHashMany task with the stack reference
     let hash_task = PoseidonHashMany::new(inputs);
-    PoseidonHashMany::push_input(inputs, &mut stack);
-    stack.push_task(hash_task);
+    PoseidonHashMany::push_input(inputs, stack.as_mut()).unwrap();
+    stack.push_task(hash_task).unwrap();
 
     // Execute until completion
-    let mut steps = 0;
-    while !stack.is_empty_back() {
-        stack.execute();
-        steps += 1;
-    }
+    let steps = run(&mut stack);
 
     // Get the result from the stack
-    let bytes = stack.borrow_front();
-    let result = Felt::from_bytes_be_slice(bytes);
-    stack.pop_front();
-    stack.pop_front();
-    stack.pop_front();
+    let [result, _, _]: [Felt; 3] = pop_result(&mut stack);
 
     // Verify the result
     assert_eq!(result, expected);
     assert!(steps > 0, "Should have executed at least one step");
-    assert_eq!(stack.front_index, 0, "Stack should be empty after test");
+}
+
+/// Run every task on the stack and return the hash left by the permutation
+fn hash(stack: &mut BidirectionalStackAccount) -> Felt {
+    // One step to load the state and two for the permutation
+    assert_eq!(run(stack), 3);
+
+    let [result, _, _]: [Felt; 3] = pop_result(stack);
+    result
+}
+
+fn poseidon_hash(x: Felt, y: Felt) -> Felt {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    PoseidonHash::push_input(x, y, stack.as_mut()).unwrap();
+    stack.push_task(PoseidonHash::new()).unwrap();
+    hash(&mut stack)
+}
+
+fn poseidon_hash_single(x: Felt) -> Felt {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    PoseidonHashSingle::push_input(x, stack.as_mut()).unwrap();
+    stack.push_task(PoseidonHashSingle::new()).unwrap();
+    hash(&mut stack)
+}
+
+#[test]
+fn test_poseidon_hash_pair() {
+    let pairs = [
+        (Felt::ZERO, Felt::ZERO),
+        (Felt::ONE, Felt::TWO),
+        (
+            Felt::from_hex("0x23a77118133287637ebdcd9e87a1613e443df789558867f5ba91faf7a024204")
+                .unwrap(),
+            Felt::ZERO - Felt::ONE,
+        ),
+    ];
+    for (x, y) in pairs {
+        let result = poseidon_hash(x, y);
+        assert_eq!(
+            reference(result),
+            Poseidon::hash(&reference(x), &reference(y))
+        );
+    }
+
+    // The order of the inputs matters
+    assert_ne!(
+        poseidon_hash(Felt::ONE, Felt::TWO),
+        poseidon_hash(Felt::TWO, Felt::ONE)
+    );
+    // A pair is absorbed without the padding appended to a two-element array
+    assert_ne!(
+        reference(poseidon_hash(Felt::ONE, Felt::TWO)),
+        Poseidon::hash_array(&[reference(Felt::ONE), reference(Felt::TWO)])
+    );
+}
+
+#[test]
+fn test_poseidon_hash_single() {
+    for x in [
+        Felt::ZERO,
+        Felt::ONE,
+        Felt::from(0xdeadbeefu64),
+        Felt::ZERO - Felt::ONE,
+    ] {
+        let result = poseidon_hash_single(x);
+        assert_eq!(reference(result), Poseidon::hash_single(&reference(x)));
+    }
 }
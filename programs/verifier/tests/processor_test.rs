@@ -0,0 +1,1372 @@
+use arithmetic::{add::Add, mul::Mul};
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::Once;
+
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{
+        deserialize, ProgramResult, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS,
+    },
+    hash::hash,
+    instruction::Instruction,
+    program::MAX_RETURN_DATA,
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+};
+use utils::{AccountCast, BidirectionalStack, Executable, StackValue};
+use verifier::{
+    error::VerifierError,
+    events::{frames_hash, JobCompleted, StepsExecuted},
+    instruction::VerifierInstruction,
+    processor::process_instruction,
+    state::{
+        AccountHeader, BidirectionalStackAccount, JobStatus, Side, Verdict, VerificationResult,
+    },
+};
+
+const CAPACITY: usize = 4 * 1024;
+
+thread_local! {
+    /// Return data set by the last instruction processed on this thread
+    static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    /// Instructions invoked through CPI on this thread
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Slot reported by the clock sysvar
+const SLOT: u64 = 42;
+
+/// Syscall stubs providing sysvars and keeping the return data apart for every test thread
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT,
+            ..Clock::default()
+        };
+        unsafe { ptr::write_unaligned(var_addr as *mut Clock, clock) };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { ptr::write_unaligned(var_addr as *mut Rent, Rent::default()) };
+        SUCCESS
+    }
+}
+
+fn install_stubs() {
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(TestStubs));
+    });
+}
+
+/// Install the syscall stubs and return the data set by `process`
+fn capture_return_data(process: impl FnOnce()) -> Vec<u8> {
+    install_stubs();
+    RETURN_DATA.with(|return_data| return_data.borrow_mut().clear());
+    process();
+    RETURN_DATA.with(|return_data| return_data.take())
+}
+
+/// Install the syscall stubs and return the instructions `process` invoked
+fn capture_invoked(process: impl FnOnce()) -> Vec<Instruction> {
+    install_stubs();
+    INVOKED.with(|invoked| invoked.borrow_mut().clear());
+    process();
+    INVOKED.with(|invoked| invoked.take())
+}
+
+/// Accounts and their backing storage for one processor call
+struct Accounts {
+    program_id: Pubkey,
+    account: Pubkey,
+    owner: Pubkey,
+    writable: bool,
+    authority: Pubkey,
+    authority_signed: bool,
+    result: Pubkey,
+    result_owner: Pubkey,
+    lamports: [u64; 4],
+    data: Vec<u8>,
+    result_data: Vec<u8>,
+}
+
+impl Accounts {
+    fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        Self {
+            program_id,
+            account,
+            owner: program_id,
+            writable: true,
+            authority: Pubkey::new_unique(),
+            authority_signed: true,
+            result: VerificationResult::find_address(&program_id, &account, &[0; 32]).0,
+            result_owner: system_program::id(),
+            lamports: [1_000, 10_000, 0, 0],
+            data: vec![0; AccountHeader::account_space(CAPACITY)],
+            // Account creation is not simulated, the result account is sized up front
+            result_data: vec![0; VerificationResult::SIZE],
+        }
+    }
+
+    fn process(&mut self, instruction: VerifierInstruction) -> Result<(), ProgramError> {
+        let [account_lamports, authority_lamports, result_lamports, system_lamports] =
+            &mut self.lamports;
+        let mut authority_data = [];
+        let mut system_data = [];
+        let system_program_id = system_program::id();
+        let accounts = [
+            AccountInfo::new(
+                &self.account,
+                false,
+                self.writable,
+                account_lamports,
+                &mut self.data,
+                &self.owner,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &self.authority,
+                self.authority_signed,
+                true,
+                authority_lamports,
+                &mut authority_data,
+                &self.program_id,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &self.result,
+                false,
+                true,
+                result_lamports,
+                &mut self.result_data,
+                &self.result_owner,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &system_program_id,
+                false,
+                false,
+                system_lamports,
+                &mut system_data,
+                &system_program_id,
+                true,
+                0,
+            ),
+        ];
+
+        let instruction_data = borsh::to_vec(&instruction).unwrap();
+        process_instruction(&self.program_id, &accounts, &instruction_data)
+    }
+
+    fn initialized() -> Self {
+        let mut accounts = Self::new();
+        accounts.process(VerifierInstruction::Initialize).unwrap();
+        accounts
+    }
+
+    fn header(&self) -> &AccountHeader {
+        AccountHeader::split(&self.data).unwrap().0
+    }
+
+    /// Point the result account at the address derived from the job and its inputs
+    fn derive_result(&mut self) -> u8 {
+        let (result, bump) = VerificationResult::find_address(
+            &self.program_id,
+            &self.account,
+            &self.header().inputs_hash,
+        );
+        self.result = result;
+        bump
+    }
+}
+
+/// Account passed to [`serialize_input`] as `(key, is_signer, is_writable, lamports, data, owner)`
+type SerializedAccount<'a> = (Pubkey, bool, bool, u64, &'a [u8], Pubkey);
+
+/// Serialize accounts and instruction data the way the runtime passes them to a program
+///
+/// Only accounts deserialized from this layout can be reallocated.
+fn serialize_input(
+    program_id: &Pubkey,
+    accounts: &[SerializedAccount],
+    instruction_data: &[u8],
+) -> Vec<u64> {
+    let mut input = Vec::new();
+    input.extend_from_slice(&(accounts.len() as u64).to_le_bytes());
+    for (key, is_signer, is_writable, lamports, data, owner) in accounts {
+        input.extend_from_slice(&[NON_DUP_MARKER, *is_signer as u8, *is_writable as u8, 0]);
+        // Original data length, filled in by `deserialize`
+        input.extend_from_slice(&[0; 4]);
+        input.extend_from_slice(key.as_ref());
+        input.extend_from_slice(owner.as_ref());
+        input.extend_from_slice(&lamports.to_le_bytes());
+        input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        input.extend_from_slice(data);
+        input.resize(
+            (input.len() + MAX_PERMITTED_DATA_INCREASE).next_multiple_of(8),
+            0,
+        );
+        // Rent epoch
+        input.extend_from_slice(&0u64.to_le_bytes());
+    }
+    input.extend_from_slice(&(instruction_data.len() as u64).to_le_bytes());
+    input.extend_from_slice(instruction_data);
+    input.extend_from_slice(program_id.as_ref());
+
+    // Keep the buffer aligned as the runtime does
+    input
+        .chunks(8)
+        .map(|chunk| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(word)
+        })
+        .collect()
+}
+
+#[test]
+fn test_initialize_writes_header() {
+    let mut accounts = Accounts::new();
+    accounts.process(VerifierInstruction::Initialize).unwrap();
+
+    let (header, stack) = AccountHeader::split(&accounts.data).unwrap();
+    header.validate().unwrap();
+    assert_eq!(header.authority, accounts.authority);
+    assert_eq!(header.status().unwrap(), JobStatus::Created);
+    assert_eq!(stack.capacity(), CAPACITY);
+    assert!(stack.is_empty_front());
+    assert!(stack.is_empty_back());
+}
+
+#[test]
+fn test_initialize_twice_is_rejected() {
+    let mut accounts = Accounts::new();
+    accounts.process(VerifierInstruction::Initialize).unwrap();
+    accounts
+        .process(VerifierInstruction::PushData(vec![1, 2, 3]))
+        .unwrap();
+
+    let authority = accounts.authority;
+    accounts.authority = Pubkey::new_unique();
+    assert_eq!(
+        accounts.process(VerifierInstruction::Initialize),
+        Err(VerifierError::AlreadyInitialized.into())
+    );
+
+    // The job in progress is left alone
+    assert_eq!(accounts.header().authority, authority);
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert_eq!(stack.borrow_front().unwrap(), &[1, 2, 3]);
+}
+
+#[test]
+fn test_initialize_requires_authority_signature() {
+    let mut accounts = Accounts::new();
+    accounts.authority_signed = false;
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::Initialize),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+
+    // Nothing is written, so the account can still be initialized by its authority
+    assert!(accounts.data.iter().all(|&byte| byte == 0));
+    accounts.authority_signed = true;
+    accounts.process(VerifierInstruction::Initialize).unwrap();
+}
+
+#[test]
+fn test_uninitialized_account_is_rejected() {
+    let mut accounts = Accounts::new();
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushData(vec![1, 2, 3])),
+        Err(VerifierError::NotInitialized.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushTask(
+            Add::new(1, 2).to_vec_with_type_tag()
+        )),
+        Err(VerifierError::NotInitialized.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::Execute),
+        Err(VerifierError::NotInitialized.into())
+    );
+}
+
+#[test]
+fn test_corrupted_header_is_rejected() {
+    let mut accounts = Accounts::new();
+    accounts.process(VerifierInstruction::Initialize).unwrap();
+    accounts.data[0] ^= 0xFF;
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::Execute),
+        Err(VerifierError::InvalidMagic.into())
+    );
+}
+
+#[test]
+fn test_job_status_follows_execution() {
+    let mut accounts = Accounts::new();
+    accounts.process(VerifierInstruction::Initialize).unwrap();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Created);
+
+    accounts.process(VerifierInstruction::Execute).unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Running);
+
+    while accounts.header().status().unwrap() == JobStatus::Running {
+        accounts.process(VerifierInstruction::Execute).unwrap();
+    }
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Finished);
+
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert_eq!(stack.peek_value::<u128>().unwrap(), 35);
+
+    // A finished job keeps its results and takes no more work
+    assert_eq!(
+        accounts.process(VerifierInstruction::Execute),
+        Err(VerifierError::JobFinished.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushData(vec![1])),
+        Err(VerifierError::JobFinished.into())
+    );
+}
+
+#[test]
+fn test_account_owned_by_another_program_is_rejected() {
+    let mut accounts = Accounts::new();
+    accounts.owner = Pubkey::new_unique();
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::Initialize),
+        Err(VerifierError::InvalidOwner.into())
+    );
+
+    let mut accounts = Accounts::initialized();
+    accounts.owner = Pubkey::new_unique();
+    for instruction in [
+        VerifierInstruction::PushData(vec![1]),
+        VerifierInstruction::PushTask(Add::new(1, 2).to_vec_with_type_tag()),
+        VerifierInstruction::Execute,
+    ] {
+        assert_eq!(
+            accounts.process(instruction),
+            Err(VerifierError::InvalidOwner.into())
+        );
+    }
+}
+
+#[test]
+fn test_read_only_account_is_rejected() {
+    let mut accounts = Accounts::initialized();
+    accounts.writable = false;
+
+    for instruction in [
+        VerifierInstruction::Initialize,
+        VerifierInstruction::PushData(vec![1]),
+        VerifierInstruction::PushTask(Add::new(1, 2).to_vec_with_type_tag()),
+        VerifierInstruction::Execute,
+    ] {
+        assert_eq!(
+            accounts.process(instruction),
+            Err(VerifierError::AccountNotWritable.into())
+        );
+    }
+}
+
+#[test]
+fn test_push_requires_authority_signature() {
+    let mut accounts = Accounts::initialized();
+    accounts.authority_signed = false;
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushData(vec![1])),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushTask(
+            Add::new(1, 2).to_vec_with_type_tag()
+        )),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+}
+
+#[test]
+fn test_push_from_other_signer_is_rejected() {
+    let mut accounts = Accounts::initialized();
+    accounts.authority = Pubkey::new_unique();
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushData(vec![1])),
+        Err(VerifierError::InvalidAuthority.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushTask(
+            Add::new(1, 2).to_vec_with_type_tag()
+        )),
+        Err(VerifierError::InvalidAuthority.into())
+    );
+
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert!(stack.is_empty_front());
+    assert!(stack.is_empty_back());
+}
+
+#[test]
+fn test_batch_push_matches_single_pushes() {
+    let tasks = vec![
+        Add::new(1, 2).to_vec_with_type_tag(),
+        Mul::new(3, 4).to_vec_with_type_tag(),
+    ];
+    let frames = vec![vec![1], vec![2, 3], vec![4, 5, 6]];
+
+    let mut single = Accounts::initialized();
+    for task in &tasks {
+        single
+            .process(VerifierInstruction::PushTask(task.clone()))
+            .unwrap();
+    }
+    for frame in &frames {
+        single
+            .process(VerifierInstruction::PushData(frame.clone()))
+            .unwrap();
+    }
+
+    let mut batch = Accounts::initialized();
+    batch
+        .process(VerifierInstruction::PushTaskBatch(tasks))
+        .unwrap();
+    batch
+        .process(VerifierInstruction::PushDataBatch(frames))
+        .unwrap();
+
+    let (_, single_stack) = AccountHeader::split(&single.data).unwrap();
+    let (_, batch_stack) = AccountHeader::split(&batch.data).unwrap();
+    assert_eq!(batch_stack.front_index, single_stack.front_index);
+    assert_eq!(batch_stack.back_index, single_stack.back_index);
+    assert_eq!(&batch_stack.buffer, &single_stack.buffer);
+    assert_eq!(batch_stack.borrow_front().unwrap(), &[4, 5, 6]);
+    assert_eq!(
+        batch_stack.borrow_back().unwrap()[..4],
+        Mul::TYPE_TAG.to_be_bytes()
+    );
+}
+
+#[test]
+fn test_batch_push_requires_authority_signature() {
+    let mut accounts = Accounts::initialized();
+    accounts.authority_signed = false;
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushDataBatch(vec![vec![1]])),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::PushTaskBatch(vec![
+            Add::new(1, 2).to_vec_with_type_tag()
+        ])),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+}
+
+#[test]
+fn test_chunked_frame_is_committed() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushData(vec![1, 2, 3]))
+        .unwrap();
+    let frame: Vec<u8> = (0..2000).map(|i| i as u8).collect();
+
+    for (index, chunk) in frame.chunks(900).enumerate().rev() {
+        accounts
+            .process(VerifierInstruction::WriteChunk {
+                offset: (index * 900) as u32,
+                bytes: chunk.to_vec(),
+            })
+            .unwrap();
+    }
+    accounts
+        .process(VerifierInstruction::CommitFrame {
+            length: frame.len() as u32,
+            hash: Some(hash(&frame).to_bytes()),
+        })
+        .unwrap();
+
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert_eq!(stack.borrow_front().unwrap(), &frame[..]);
+    assert_eq!(stack.borrow_front_at(1).unwrap(), &[1, 2, 3]);
+}
+
+#[test]
+fn test_chunked_frame_with_wrong_hash_is_rejected() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::WriteChunk {
+            offset: 0,
+            bytes: vec![1, 2, 3],
+        })
+        .unwrap();
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::CommitFrame {
+            length: 3,
+            hash: Some(hash(&[1, 2, 4]).to_bytes()),
+        }),
+        Err(VerifierError::HashMismatch.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::WriteChunk {
+            offset: CAPACITY as u32,
+            bytes: vec![1],
+        }),
+        Err(VerifierError::StackCapacity.into())
+    );
+
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert!(stack.is_empty_front());
+}
+
+#[test]
+fn test_chunked_upload_requires_authority_signature() {
+    let mut accounts = Accounts::initialized();
+    accounts.authority_signed = false;
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::WriteChunk {
+            offset: 0,
+            bytes: vec![1],
+        }),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+    assert_eq!(
+        accounts.process(VerifierInstruction::CommitFrame {
+            length: 1,
+            hash: None,
+        }),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+}
+
+#[test]
+fn test_execute_does_not_need_authority() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Add::new(48, 52).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    accounts.authority = Pubkey::new_unique();
+    accounts.authority_signed = false;
+    accounts.process(VerifierInstruction::Execute).unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Finished);
+}
+
+#[test]
+fn test_execute_many_runs_job_to_completion() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    accounts
+        .process(VerifierInstruction::ExecuteMany {
+            max_steps: u32::MAX,
+            compute_margin: 0,
+        })
+        .unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Finished);
+
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert_eq!(stack.peek_value::<u128>().unwrap(), 35);
+}
+
+#[test]
+fn test_execute_many_stops_at_limits() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    accounts
+        .process(VerifierInstruction::ExecuteMany {
+            max_steps: 4,
+            compute_margin: 0,
+        })
+        .unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Running);
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert_eq!(stack.peek_value::<u128>().unwrap(), 10);
+
+    // Host builds report no compute units left, so only the first step runs
+    accounts
+        .process(VerifierInstruction::ExecuteMany {
+            max_steps: u32::MAX,
+            compute_margin: 1,
+        })
+        .unwrap();
+    let (_, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert!(stack.is_empty_front());
+    assert_eq!(
+        stack.borrow_back().unwrap()[..4],
+        Add::TYPE_TAG.to_be_bytes()
+    );
+}
+
+#[test]
+fn test_execute_many_reports_steps() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    let return_data = capture_return_data(|| {
+        accounts
+            .process(VerifierInstruction::ExecuteMany {
+                max_steps: 4,
+                compute_margin: 0,
+            })
+            .unwrap();
+    });
+    assert_eq!(
+        StepsExecuted::try_from_slice(&return_data).unwrap(),
+        StepsExecuted { steps: 4 }
+    );
+}
+
+#[test]
+fn test_execute_many_finishing_job_returns_result() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts
+        .process(VerifierInstruction::ExecuteMany {
+            max_steps: 4,
+            compute_margin: 0,
+        })
+        .unwrap();
+
+    let return_data = capture_return_data(|| {
+        accounts
+            .process(VerifierInstruction::ExecuteMany {
+                max_steps: u32::MAX,
+                compute_margin: 0,
+            })
+            .unwrap();
+    });
+    let steps = accounts.header().steps;
+    assert_eq!(steps, 1 + 2 * 7);
+
+    let frames = vec![35u128.to_frame()];
+    assert_eq!(
+        JobCompleted::try_from_slice(&return_data).unwrap(),
+        JobCompleted {
+            job: accounts.account,
+            steps,
+            frames_hash: frames_hash(&frames).to_bytes(),
+            frames: Some(frames),
+        }
+    );
+}
+
+#[test]
+fn test_finished_job_emits_result() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts
+        .process(VerifierInstruction::ExecuteMany {
+            max_steps: 2 * 7,
+            compute_margin: 0,
+        })
+        .unwrap();
+    assert_eq!(accounts.header().steps, 2 * 7);
+
+    let return_data = capture_return_data(|| {
+        accounts.process(VerifierInstruction::Execute).unwrap();
+    });
+    let steps = accounts.header().steps;
+    assert_eq!(steps, 1 + 2 * 7);
+
+    let frames = vec![35u128.to_frame()];
+    assert_eq!(
+        JobCompleted::try_from_slice(&return_data).unwrap(),
+        JobCompleted {
+            job: accounts.account,
+            steps,
+            frames_hash: frames_hash(&frames).to_bytes(),
+            frames: Some(frames),
+        }
+    );
+}
+
+#[test]
+fn test_running_job_sets_no_result() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    let return_data = capture_return_data(|| {
+        accounts.process(VerifierInstruction::Execute).unwrap();
+    });
+    assert!(return_data.is_empty());
+}
+
+#[test]
+fn test_large_result_is_only_hashed() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_front(&[1; 600]).unwrap();
+    stack.push_front(&[2; 600]).unwrap();
+
+    let job = Pubkey::new_unique();
+    let event = JobCompleted::new(job, 3, &stack).unwrap();
+    assert_eq!(event.frames, None);
+    assert_eq!(
+        event.frames_hash,
+        frames_hash(&[vec![2; 600], vec![1; 600]]).to_bytes()
+    );
+}
+
+#[test]
+fn test_result_filling_return_data_is_included() {
+    let job = Pubkey::new_unique();
+    let empty =
+        JobCompleted::new(job, 3, &BidirectionalStackAccount::with_capacity(CAPACITY)).unwrap();
+    assert_eq!(empty.frames, Some(vec![]));
+    let overhead = borsh::object_length(&empty).unwrap() + size_of::<u32>();
+
+    // Largest frame whose event still fits, and one byte more
+    for (length, fits) in [
+        (MAX_RETURN_DATA - overhead, true),
+        (MAX_RETURN_DATA - overhead + 1, false),
+    ] {
+        let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+        stack.push_front(&vec![7; length]).unwrap();
+
+        let event = JobCompleted::new(job, 3, &stack).unwrap();
+        assert_eq!(event.frames.is_some(), fits);
+        if fits {
+            assert_eq!(borsh::object_length(&event).unwrap(), MAX_RETURN_DATA);
+        }
+    }
+}
+
+#[test]
+fn test_inputs_hash_commits_to_pushes() {
+    let task = Mul::new(5, 7).to_vec_with_type_tag();
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushData(vec![1, 2]))
+        .unwrap();
+    accounts
+        .process(VerifierInstruction::PushTaskBatch(vec![task.clone()]))
+        .unwrap();
+
+    let mut expected = *accounts.header();
+    expected.inputs_hash = [0; 32];
+    expected.record_input(Side::Front, &[1, 2]);
+    expected.record_input(Side::Back, &task);
+    assert_eq!(accounts.header().inputs_hash, expected.inputs_hash);
+
+    // The same frames pushed to the other sides give another commitment
+    let mut swapped = Accounts::initialized();
+    swapped
+        .process(VerifierInstruction::PushTask(vec![1, 2]))
+        .unwrap();
+    swapped
+        .process(VerifierInstruction::PushDataBatch(vec![task]))
+        .unwrap();
+    assert_ne!(swapped.header().inputs_hash, expected.inputs_hash);
+}
+
+#[test]
+fn test_finalize_records_result_and_closes_job() {
+    install_stubs();
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts
+        .process(VerifierInstruction::ExecuteMany {
+            max_steps: u32::MAX,
+            compute_margin: 0,
+        })
+        .unwrap();
+    let inputs_hash = accounts.header().inputs_hash;
+    let bump = accounts.derive_result();
+
+    let invoked = capture_invoked(|| accounts.process(VerifierInstruction::Finalize).unwrap());
+    assert_eq!(
+        invoked,
+        [system_instruction::create_account(
+            &accounts.authority,
+            &accounts.result,
+            Rent::default().minimum_balance(VerificationResult::SIZE),
+            VerificationResult::SIZE as u64,
+            &accounts.program_id,
+        )]
+    );
+
+    let result = VerificationResult::cast(&accounts.result_data).unwrap();
+    assert_eq!(
+        *result,
+        VerificationResult {
+            magic: VerificationResult::MAGIC,
+            version: VerificationResult::VERSION,
+            verdict: Verdict::Accepted as u8,
+            bump,
+            _padding: [0; 2],
+            job: accounts.account,
+            slot: SLOT,
+            inputs_commitment: inputs_hash,
+            outputs_commitment: frames_hash(&[35u128.to_frame()]).to_bytes(),
+        }
+    );
+
+    // The job account is emptied and its rent refunded to the authority
+    assert_eq!(accounts.lamports[..2], [0, 11_000]);
+    assert!(accounts.data.iter().all(|byte| *byte == 0));
+}
+
+/// Accounts holding a finished job, with the result account at its derived address
+fn finished_job() -> Accounts {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Add::new(1, 2).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts.process(VerifierInstruction::Execute).unwrap();
+    accounts.derive_result();
+    accounts
+}
+
+#[test]
+fn test_finalize_takes_over_funded_result_account() {
+    let mut accounts = finished_job();
+    accounts.lamports[2] = 100;
+
+    let invoked = capture_invoked(|| accounts.process(VerifierInstruction::Finalize).unwrap());
+    assert_eq!(
+        invoked,
+        [
+            system_instruction::transfer(
+                &accounts.authority,
+                &accounts.result,
+                Rent::default().minimum_balance(VerificationResult::SIZE) - 100,
+            ),
+            system_instruction::allocate(&accounts.result, VerificationResult::SIZE as u64),
+            system_instruction::assign(&accounts.result, &accounts.program_id),
+        ]
+    );
+    assert_eq!(
+        VerificationResult::cast(&accounts.result_data).unwrap().job,
+        accounts.account
+    );
+}
+
+#[test]
+fn test_finalize_rewrites_result_of_same_inputs() {
+    let mut accounts = finished_job();
+    accounts.result_owner = accounts.program_id;
+    accounts.result_data.fill(0xAA);
+
+    let invoked = capture_invoked(|| accounts.process(VerifierInstruction::Finalize).unwrap());
+    assert!(invoked.is_empty());
+
+    let result = VerificationResult::cast(&accounts.result_data).unwrap();
+    assert_eq!(result.magic, VerificationResult::MAGIC);
+    assert_eq!(result.job, accounts.account);
+    assert_eq!(result.slot, SLOT);
+}
+
+#[test]
+fn test_result_address_commits_to_inputs() {
+    let program_id = Pubkey::new_unique();
+    let job = Pubkey::new_unique();
+
+    assert_ne!(
+        VerificationResult::find_address(&program_id, &job, &[1; 32]),
+        VerificationResult::find_address(&program_id, &job, &[2; 32])
+    );
+}
+
+#[test]
+fn test_finalize_requires_finished_job() {
+    install_stubs();
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::Finalize),
+        Err(VerifierError::JobNotFinished.into())
+    );
+    accounts.process(VerifierInstruction::Execute).unwrap();
+    assert_eq!(
+        accounts.process(VerifierInstruction::Finalize),
+        Err(VerifierError::JobNotFinished.into())
+    );
+}
+
+#[test]
+fn test_finalize_checks_result_address_and_authority() {
+    install_stubs();
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Add::new(1, 2).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts.process(VerifierInstruction::Execute).unwrap();
+
+    accounts.derive_result();
+    let result = accounts.result;
+    accounts.result = Pubkey::new_unique();
+    assert_eq!(
+        accounts.process(VerifierInstruction::Finalize),
+        Err(VerifierError::InvalidResultAccount.into())
+    );
+
+    accounts.result = result;
+    accounts.authority_signed = false;
+    assert_eq!(
+        accounts.process(VerifierInstruction::Finalize),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Finished);
+}
+
+#[test]
+fn test_reset_clears_job_and_keeps_authority() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushData(vec![1, 2, 3]))
+        .unwrap();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts.process(VerifierInstruction::Execute).unwrap();
+
+    accounts.process(VerifierInstruction::Reset).unwrap();
+    let (header, stack) = AccountHeader::split(&accounts.data).unwrap();
+    assert_eq!(header.authority, accounts.authority);
+    assert_eq!(header.status().unwrap(), JobStatus::Created);
+    assert_eq!(header.steps, 0);
+    assert_eq!(header.inputs_hash, [0; 32]);
+    assert!(stack.is_empty_front());
+    assert!(stack.is_empty_back());
+
+    // The job can be pushed again from scratch
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Add::new(1, 2).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts.process(VerifierInstruction::Execute).unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Finished);
+}
+
+#[test]
+fn test_reset_requires_authority_signature() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushData(vec![1, 2, 3]))
+        .unwrap();
+    accounts.authority_signed = false;
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::Reset),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+    assert!(!AccountHeader::split(&accounts.data)
+        .unwrap()
+        .1
+        .is_empty_front());
+}
+
+#[test]
+fn test_aborted_job_is_rejected_until_reset() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    accounts
+        .process(VerifierInstruction::Abort { reason: 7 })
+        .unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Failed);
+    assert_eq!(accounts.header().failure_code, 7);
+
+    for instruction in [
+        VerifierInstruction::Execute,
+        VerifierInstruction::PushData(vec![1]),
+        VerifierInstruction::Abort { reason: 8 },
+    ] {
+        assert_eq!(
+            accounts.process(instruction),
+            Err(VerifierError::JobAborted(7).into())
+        );
+    }
+
+    accounts.process(VerifierInstruction::Reset).unwrap();
+    assert_eq!(accounts.header().status().unwrap(), JobStatus::Created);
+    assert_eq!(accounts.header().failure_code, 0);
+}
+
+#[test]
+fn test_abort_requires_authority_and_pending_job() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Add::new(1, 2).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+
+    accounts.authority_signed = false;
+    assert_eq!(
+        accounts.process(VerifierInstruction::Abort { reason: 1 }),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+
+    accounts.authority_signed = true;
+    accounts.process(VerifierInstruction::Execute).unwrap();
+    assert_eq!(
+        accounts.process(VerifierInstruction::Abort { reason: 1 }),
+        Err(VerifierError::JobFinished.into())
+    );
+}
+
+#[test]
+fn test_close_moves_lamports_to_recipient() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushData(vec![1, 2, 3]))
+        .unwrap();
+
+    accounts.process(VerifierInstruction::Close).unwrap();
+    assert_eq!(accounts.lamports[..3], [0, 10_000, 1_000]);
+    assert!(accounts.data.iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn test_close_checks_authority_and_recipient() {
+    let mut accounts = Accounts::initialized();
+    accounts.authority_signed = false;
+    assert_eq!(
+        accounts.process(VerifierInstruction::Close),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+
+    accounts.authority_signed = true;
+    accounts.result = accounts.account;
+    assert_eq!(
+        accounts.process(VerifierInstruction::Close),
+        Err(VerifierError::InvalidRecipient.into())
+    );
+    assert_eq!(accounts.lamports[0], 1_000);
+}
+
+#[test]
+fn test_create_job_initializes_derived_account() {
+    install_stubs();
+    let mut accounts = Accounts::new();
+    accounts.account =
+        AccountHeader::find_job_address(&accounts.program_id, &accounts.authority, 7).0;
+    accounts.lamports[0] = 0;
+
+    let invoked = capture_invoked(|| {
+        accounts
+            .process(VerifierInstruction::CreateJob {
+                job_id: 7,
+                capacity: CAPACITY as u32,
+            })
+            .unwrap();
+    });
+    assert_eq!(
+        invoked,
+        [system_instruction::create_account(
+            &accounts.authority,
+            &accounts.account,
+            Rent::default().minimum_balance(AccountHeader::account_space(CAPACITY)),
+            AccountHeader::account_space(CAPACITY) as u64,
+            &accounts.program_id,
+        )]
+    );
+    let (header, stack) = AccountHeader::split(&accounts.data).unwrap();
+    header.validate().unwrap();
+    assert_eq!(header.authority, accounts.authority);
+    assert_eq!(header.status().unwrap(), JobStatus::Created);
+    assert!(stack.is_empty_back());
+}
+
+#[test]
+fn test_create_job_takes_over_funded_address() {
+    let mut accounts = Accounts::new();
+    accounts.account =
+        AccountHeader::find_job_address(&accounts.program_id, &accounts.authority, 7).0;
+    let space = AccountHeader::account_space(CAPACITY);
+
+    let invoked = capture_invoked(|| {
+        accounts
+            .process(VerifierInstruction::CreateJob {
+                job_id: 7,
+                capacity: CAPACITY as u32,
+            })
+            .unwrap();
+    });
+    assert_eq!(
+        invoked,
+        [
+            system_instruction::transfer(
+                &accounts.authority,
+                &accounts.account,
+                Rent::default().minimum_balance(space) - accounts.lamports[0],
+            ),
+            system_instruction::allocate(&accounts.account, space as u64),
+            system_instruction::assign(&accounts.account, &accounts.program_id),
+        ]
+    );
+    accounts.header().validate().unwrap();
+}
+
+#[test]
+fn test_create_job_checks_address_and_signature() {
+    install_stubs();
+    let mut accounts = Accounts::new();
+    accounts.account =
+        AccountHeader::find_job_address(&accounts.program_id, &accounts.authority, 7).0;
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::CreateJob {
+            job_id: 8,
+            capacity: CAPACITY as u32,
+        }),
+        Err(VerifierError::InvalidJobAccount.into())
+    );
+    accounts.authority_signed = false;
+    assert_eq!(
+        accounts.process(VerifierInstruction::CreateJob {
+            job_id: 7,
+            capacity: CAPACITY as u32,
+        }),
+        Err(VerifierError::MissingAuthoritySignature.into())
+    );
+    assert!(accounts.data.iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn test_grow_job_extends_empty_stack() {
+    install_stubs();
+    let accounts = Accounts::initialized();
+    let instruction_data = borsh::to_vec(&VerifierInstruction::GrowJob {
+        capacity: (CAPACITY + 2 * MAX_PERMITTED_DATA_INCREASE) as u32,
+    })
+    .unwrap();
+    let mut input = serialize_input(
+        &accounts.program_id,
+        &[
+            (
+                accounts.account,
+                false,
+                true,
+                1_000,
+                &accounts.data,
+                accounts.program_id,
+            ),
+            (
+                accounts.authority,
+                true,
+                true,
+                10_000,
+                &[],
+                Pubkey::default(),
+            ),
+            (
+                system_program::id(),
+                false,
+                false,
+                0,
+                &[],
+                Pubkey::default(),
+            ),
+        ],
+        &instruction_data,
+    );
+    let (program_id, infos, instruction_data) =
+        unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+    // Every instruction grows the account by at most MAX_PERMITTED_DATA_INCREASE
+    process_instruction(program_id, &infos, instruction_data).unwrap();
+    let data = infos[0].try_borrow_data().unwrap();
+    assert_eq!(
+        data.len(),
+        accounts.data.len() + MAX_PERMITTED_DATA_INCREASE
+    );
+    let (header, stack) = AccountHeader::split(&data).unwrap();
+    assert_eq!(header.authority, accounts.authority);
+    assert_eq!(stack.capacity(), CAPACITY + MAX_PERMITTED_DATA_INCREASE);
+    assert!(stack.is_empty_back());
+}
+
+#[test]
+fn test_grow_job_requires_empty_stack() {
+    install_stubs();
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushData(vec![1, 2, 3]))
+        .unwrap();
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::GrowJob {
+            capacity: 2 * CAPACITY as u32,
+        }),
+        Err(VerifierError::JobNotEmpty.into())
+    );
+
+    // Nothing to do once the account holds the requested capacity
+    accounts.process(VerifierInstruction::Reset).unwrap();
+    accounts
+        .process(VerifierInstruction::GrowJob {
+            capacity: CAPACITY as u32,
+        })
+        .unwrap();
+    assert_eq!(accounts.data.len(), AccountHeader::account_space(CAPACITY));
+}
+
+#[test]
+fn test_unknown_task_is_rejected() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(vec![0xFF; 8]))
+        .unwrap();
+
+    assert_eq!(
+        accounts.process(VerifierInstruction::Execute),
+        Err(VerifierError::UnknownTask(0xFFFFFFFF).into())
+    );
+}
+
+#[test]
+fn test_task_without_its_data_is_rejected() {
+    let mut accounts = Accounts::initialized();
+    accounts
+        .process(VerifierInstruction::PushTask(
+            Mul::new(5, 7).to_vec_with_type_tag(),
+        ))
+        .unwrap();
+    accounts.process(VerifierInstruction::Execute).unwrap();
+    accounts.process(VerifierInstruction::Execute).unwrap();
+
+    // Drop the partial sum the next step expects before it runs
+    let (_, stack) = AccountHeader::split_mut(&mut accounts.data).unwrap();
+    stack.pop_front().unwrap();
+    assert_eq!(
+        accounts.process(VerifierInstruction::Execute),
+        Err(VerifierError::EmptyStack.into())
+    );
+
+    // A frame of the wrong kind is told apart from a missing one
+    let (_, stack) = AccountHeader::split_mut(&mut accounts.data).unwrap();
+    stack.push_value(&vec![1u8]).unwrap();
+    assert_eq!(
+        accounts.process(VerifierInstruction::Execute),
+        Err(VerifierError::Value(utils::ValueError::TagMismatch {
+            expected: 0x01,
+            found: 0x02,
+        })
+        .into())
+    );
+}
+
+#[test]
+fn test_error_codes_are_custom() {
+    assert_eq!(
+        ProgramError::from(VerifierError::InvalidOwner),
+        ProgramError::Custom(0)
+    );
+    assert_eq!(
+        ProgramError::from(VerifierError::MissingAuthoritySignature),
+        ProgramError::Custom(3)
+    );
+    assert_eq!(
+        ProgramError::from(VerifierError::Io(std::io::Error::other("io"))),
+        ProgramError::Custom(25)
+    );
+    assert_eq!(
+        ProgramError::from(VerifierError::UnknownTask(7)),
+        ProgramError::Custom(27)
+    );
+    assert_eq!(
+        ProgramError::from(VerifierError::HashMismatch),
+        ProgramError::Custom(28)
+    );
+    assert_eq!(
+        ProgramError::from(VerifierError::JobNotFinished),
+        ProgramError::Custom(29)
+    );
+    assert_eq!(
+        ProgramError::from(VerifierError::JobAborted(7)),
+        ProgramError::Custom(31)
+    );
+}
@@ -0,0 +1,86 @@
+use arithmetic::add::Add;
+use stark::poseidon::hades::HadesPermutation;
+use utils::{fnv1a, type_id, Decoder, Executable, TaskVisitor, TypeIdentifiable};
+use verifier::scheduler::{TASKS, TASK_MANIFEST};
+
+/// Reports the size of the task type it is dispatched to
+struct SizeOf;
+
+impl TaskVisitor for SizeOf {
+    type Output = usize;
+
+    fn visit<T: Executable>(self, _decode: Decoder<T>) -> usize {
+        std::mem::size_of::<T>()
+    }
+}
+
+#[test]
+fn test_registered_tasks() {
+    let tasks: Vec<_> = TASKS.iter().flat_map(|tasks| tasks.iter()).collect();
+    assert_eq!(tasks.len(), 18);
+
+    let add = tasks
+        .iter()
+        .find(|task| task.path == "arithmetic::add::Add")
+        .unwrap();
+    assert_eq!(add.type_tag, Add::TYPE_TAG);
+    assert_eq!(add.size, 32);
+
+    let hades = tasks
+        .iter()
+        .find(|task| task.path == "stark::poseidon::hades::HadesPermutation")
+        .unwrap();
+    assert_eq!(hades.type_tag, HadesPermutation::TYPE_TAG);
+    assert_eq!((hades.name, hades.version), ("stark.hades", 1));
+    assert_eq!(hades.legacy.len(), 1);
+}
+
+#[test]
+fn test_type_ids() {
+    // Tasks without an explicit identifier keep the tag derived from their path
+    assert_eq!(Add::TYPE_NAME, "arithmetic::add::Add");
+    assert_eq!(Add::TYPE_VERSION, 0);
+    assert_eq!(Add::TYPE_ID, fnv1a(b"arithmetic::add::Add"));
+
+    assert_eq!(HadesPermutation::TYPE_ID, type_id("stark.hades", 1));
+    assert_ne!(type_id("stark.hades", 1), type_id("stark.hades", 2));
+    assert_ne!(type_id("stark.hades", 1), type_id("stark.hades", 0));
+}
+
+#[test]
+fn test_dispatch_by_tag() {
+    assert_eq!(arithmetic::dispatch(Add::TYPE_TAG, SizeOf).ok(), Some(32));
+    assert!(stark::dispatch(Add::TYPE_TAG, SizeOf).is_err());
+    assert_eq!(
+        stark::dispatch(HadesPermutation::TYPE_TAG, SizeOf).ok(),
+        Some(std::mem::size_of::<HadesPermutation>())
+    );
+
+    let legacy_tag = fnv1a(b"stark::poseidon::hades::HadesPermutation");
+    assert_eq!(
+        stark::dispatch(legacy_tag, SizeOf).ok(),
+        Some(std::mem::size_of::<HadesPermutation>())
+    );
+}
+
+#[test]
+fn test_manifest_lists_registered_tasks() {
+    let manifest: serde_json::Value = serde_json::from_str(TASK_MANIFEST).unwrap();
+    let entries = manifest["tasks"].as_array().unwrap();
+
+    let tasks: Vec<_> = TASKS.iter().flat_map(|tasks| tasks.iter()).collect();
+    assert_eq!(entries.len(), tasks.len());
+    for (entry, task) in entries.iter().zip(tasks) {
+        assert_eq!(entry["path"], task.path);
+        assert_eq!(entry["name"], task.name);
+        assert_eq!(entry["version"], task.version);
+        assert_eq!(entry["type_tag"], task.type_tag);
+        assert_eq!(entry["size"], task.size);
+
+        let legacy = entry["legacy"].as_array().unwrap();
+        assert_eq!(legacy.len(), task.legacy.len());
+        for (entry, legacy) in legacy.iter().zip(task.legacy) {
+            assert_eq!(entry["type_tag"], legacy.type_tag);
+        }
+    }
+}
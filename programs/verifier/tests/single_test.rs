@@ -2,18 +2,19 @@ use arithmetic::*;
 use utils::{BidirectionalStack, Scheduler};
 use verifier::state::BidirectionalStackAccount;
 
+const CAPACITY: usize = 64 * 1024;
+
 #[test]
 fn test_single_exponentiation() {
-    let mut stack = BidirectionalStackAccount::default();
-    stack.push_task(exp::Exp::new(2, 10));
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_task(exp::Exp::new(2, 10)).unwrap();
 
     while !stack.is_empty_back() {
-        stack.execute();
+        stack.execute().unwrap();
     }
 
-    let result = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
+    let result: u128 = stack.pop_value().unwrap();
     assert_eq!(result, 1024);
-    stack.pop_front();
 
     // Verify stack is empty
     assert_eq!(stack.front_index, 0);
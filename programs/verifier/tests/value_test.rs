@@ -0,0 +1,71 @@
+use stark::felt::Felt;
+use utils::{BidirectionalStack, StackValue, ValueError};
+use verifier::{error::VerifierError, state::BidirectionalStackAccount};
+
+const CAPACITY: usize = 64 * 1024;
+
+#[test]
+fn test_felt_values() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    let felts = [
+        Felt::from_hex("0x1").unwrap(),
+        Felt::from_hex("0x2").unwrap(),
+    ];
+
+    stack.push_value(&felts[0]).unwrap();
+    stack.push_value(&felts).unwrap();
+    stack.push_value(&b"proof".to_vec()).unwrap();
+
+    let bytes: Vec<u8> = stack.pop_value().unwrap();
+    assert_eq!(bytes, b"proof");
+    let array: [Felt; 2] = stack.pop_value().unwrap();
+    assert_eq!(array, felts);
+    let felt: Felt = stack.pop_value().unwrap();
+    assert_eq!(felt, felts[0]);
+
+    assert!(stack.is_empty_front());
+}
+
+#[test]
+fn test_pop_wrong_value() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_value(&42u128).unwrap();
+
+    let result = stack.pop_value::<Felt>();
+    assert!(matches!(
+        result,
+        Err(VerifierError::Value(ValueError::TagMismatch { expected, found }))
+            if expected == Felt::VALUE_TAG && found == u128::VALUE_TAG
+    ));
+
+    // The mismatched frame stays on the stack
+    let value: u128 = stack.pop_value().unwrap();
+    assert_eq!(value, 42);
+}
+
+#[test]
+fn test_pop_wrong_array_length() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+    stack.push_value(&[Felt::ZERO; 2]).unwrap();
+
+    assert!(matches!(
+        stack.pop_value::<[Felt; 3]>(),
+        Err(VerifierError::Value(ValueError::InvalidLength {
+            expected: 96,
+            found: 64
+        }))
+    ));
+}
+
+#[test]
+fn test_untagged_data_is_rejected() {
+    let mut stack = BidirectionalStackAccount::with_capacity(CAPACITY);
+
+    // Raw bytes pushed without a value tag, the way data used to be pushed
+    stack.push_front(&100u128.to_be_bytes()).unwrap();
+
+    assert!(matches!(
+        stack.pop_value::<u128>(),
+        Err(VerifierError::Value(ValueError::TagMismatch { .. }))
+    ));
+}
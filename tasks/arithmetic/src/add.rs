@@ -1,5 +1,5 @@
 use utils::{impl_type_identifiable, BidirectionalStack};
-use utils::{Executable, TypeIdentifiable};
+use utils::{Executable, Pod, TypeIdentifiable};
 
 #[repr(C)]
 pub struct Add {
@@ -9,6 +9,8 @@ pub struct Add {
 
 impl_type_identifiable!(Add);
 
+unsafe impl Pod for Add {}
+
 impl Add {
     pub fn new(x: u128, y: u128) -> Self {
         Self { x, y }
@@ -20,13 +22,16 @@ impl Add {
 }
 
 impl Executable for Add {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
         let result = self.compute();
 
-        // Convert result to bytes and push to stack
-        stack.push_front(&result.to_be_bytes()).unwrap();
+        // Push the result to the stack
+        stack.push_value(&result)?;
 
-        Vec::new()
+        Ok(Vec::new())
     }
 
     fn is_finished(&mut self) -> bool {
@@ -1,6 +1,6 @@
 use crate::mul::Mul;
 use utils::{impl_type_identifiable, BidirectionalStack};
-use utils::{Executable, TypeIdentifiable};
+use utils::{Executable, Pod, TypeIdentifiable};
 
 #[repr(C)]
 pub struct Factorial {
@@ -9,6 +9,8 @@ pub struct Factorial {
 
 impl_type_identifiable!(Factorial);
 
+unsafe impl Pod for Factorial {}
+
 impl Factorial {
     pub fn new(n: u128) -> Self {
         Self { n }
@@ -24,6 +26,8 @@ pub struct FactorialInternal {
 
 impl_type_identifiable!(FactorialInternal);
 
+unsafe impl Pod for FactorialInternal {}
+
 impl FactorialInternal {
     pub fn new(result: u128, current: u128, max: u128) -> Self {
         Self { result, current, max }
@@ -31,24 +35,26 @@ impl FactorialInternal {
 }
 
 impl Executable for FactorialInternal {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
-        // Get the result of the previous multiplication
-        let mul_result = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        // Pop the result of the previous multiplication
+        let mul_result: u128 = stack.pop_value()?;
 
         // Update internal state
         self.result = mul_result;
         self.current += 1;
 
-        // Remove the result from the stack
-        stack.pop_front();
-
         if self.current <= self.max {
             // Continue multiplying by creating another Mul task
-            vec![Mul::new(self.result, self.current).to_vec_with_type_tag()]
+            Ok(vec![
+                Mul::new(self.result, self.current).to_vec_with_type_tag()
+            ])
         } else {
             // We're done, push the final result
-            stack.push_front(&self.result.to_be_bytes()).unwrap();
-            Vec::new()
+            stack.push_value(&self.result)?;
+            Ok(Vec::new())
         }
     }
 
@@ -58,18 +64,21 @@ impl Executable for FactorialInternal {
 }
 
 impl Executable for Factorial {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
         if self.n == 0 || self.n == 1 {
             // Special case: 0! = 1! = 1
-            stack.push_front(&1u128.to_be_bytes()).unwrap();
-            Vec::new()
+            stack.push_value(&1u128)?;
+            Ok(Vec::new())
         } else {
             // Create tasks for first multiplication (1 * 2)
             // and tracking factorial progress
-            vec![
+            Ok(vec![
                 Mul::new(1, 2).to_vec_with_type_tag(),
                 FactorialInternal::new(1, 2, self.n).to_vec_with_type_tag(),
-            ]
+            ])
         }
     }
 
@@ -1,5 +1,5 @@
 use utils::{impl_type_identifiable, BidirectionalStack};
-use utils::{Executable, TypeIdentifiable};
+use utils::{Executable, Pod, TypeIdentifiable};
 
 #[repr(C)]
 pub struct Fibonacci {
@@ -8,6 +8,8 @@ pub struct Fibonacci {
 
 impl_type_identifiable!(Fibonacci);
 
+unsafe impl Pod for Fibonacci {}
+
 impl Fibonacci {
     pub fn new(n: u32) -> Self {
         Self { n }
@@ -21,6 +23,8 @@ pub struct FibonacciCombiner {
 
 impl_type_identifiable!(FibonacciCombiner);
 
+unsafe impl Pod for FibonacciCombiner {}
+
 impl FibonacciCombiner {
     pub fn new(n: u32) -> Self {
         Self { n }
@@ -28,25 +32,28 @@ impl FibonacciCombiner {
 }
 
 impl Executable for Fibonacci {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
         match self.n {
             0 => {
                 // Base case: F(0) = 0
-                stack.push_front(&0u128.to_be_bytes()).unwrap();
-                Vec::new()
+                stack.push_value(&0u128)?;
+                Ok(Vec::new())
             }
             1 => {
                 // Base case: F(1) = 1
-                stack.push_front(&1u128.to_be_bytes()).unwrap();
-                Vec::new()
+                stack.push_value(&1u128)?;
+                Ok(Vec::new())
             }
             n => {
                 // Recursive case: F(n) = F(n-1) + F(n-2)
-                vec![
+                Ok(vec![
                     Fibonacci::new(n - 1).to_vec_with_type_tag(),
                     Fibonacci::new(n - 2).to_vec_with_type_tag(),
                     FibonacciCombiner::new(n).to_vec_with_type_tag(),
-                ]
+                ])
             }
         }
     }
@@ -57,21 +64,21 @@ impl Executable for Fibonacci {
 }
 
 impl Executable for FibonacciCombiner {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
         // Pop F(n-2) and F(n-1) from the stack
-        let fib_n_2 = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
-        stack.pop_front();
-
-        let fib_n_1 = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
-        stack.pop_front();
+        let fib_n_2: u128 = stack.pop_value()?;
+        let fib_n_1: u128 = stack.pop_value()?;
 
         // Compute F(n) = F(n-1) + F(n-2)
         let result = fib_n_1.saturating_add(fib_n_2);
 
         // Push the result back to the stack
-        stack.push_front(&result.to_be_bytes()).unwrap();
+        stack.push_value(&result)?;
 
-        Vec::new()
+        Ok(Vec::new())
     }
 
     fn is_finished(&mut self) -> bool {
@@ -3,3 +3,15 @@ pub mod exp;
 pub mod fact;
 pub mod fib;
 pub mod mul;
+
+utils::register_tasks! {
+    add::Add,
+    exp::Exp,
+    exp::ExpInternal,
+    fact::Factorial,
+    fact::FactorialInternal,
+    fib::Fibonacci,
+    fib::FibonacciCombiner,
+    mul::Mul,
+    mul::MulInternal,
+}
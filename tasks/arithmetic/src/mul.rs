@@ -1,6 +1,6 @@
 use crate::add::Add;
 use utils::{impl_type_identifiable, BidirectionalStack};
-use utils::{Executable, TypeIdentifiable};
+use utils::{Executable, Pod, TypeIdentifiable};
 
 #[repr(C)]
 pub struct Mul {
@@ -10,6 +10,8 @@ pub struct Mul {
 
 impl_type_identifiable!(Mul);
 
+unsafe impl Pod for Mul {}
+
 impl Mul {
     pub fn new(x: u128, y: u128) -> Self {
         Self { x, y }
@@ -26,6 +28,8 @@ pub struct MulInternal {
 
 impl_type_identifiable!(MulInternal);
 
+unsafe impl Pod for MulInternal {}
+
 impl MulInternal {
     pub fn new(x: u128, y: u128, result: u128, counter: u128) -> Self {
         Self {
@@ -38,24 +42,24 @@ impl MulInternal {
 }
 
 impl Executable for MulInternal {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
-        // Get the result of the previous addition
-        let add_result = u128::from_be_bytes(stack.borrow_front().try_into().unwrap());
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        // Pop the result of the previous addition
+        let add_result: u128 = stack.pop_value()?;
 
         // Update internal state
         self.counter += 1;
         self.result = add_result;
 
-        // Remove the result from the stack
-        stack.pop_front();
-
         if self.counter < self.y {
             // Continue adding by creating another Add task
-            vec![Add::new(self.result, self.x).to_vec_with_type_tag()]
+            Ok(vec![Add::new(self.result, self.x).to_vec_with_type_tag()])
         } else {
             // We're done, push the final result
-            stack.push_front(&self.result.to_be_bytes()).unwrap();
-            Vec::new()
+            stack.push_value(&self.result)?;
+            Ok(Vec::new())
         }
     }
 
@@ -65,17 +69,20 @@ impl Executable for MulInternal {
 }
 
 impl Executable for Mul {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
         if self.y == 0 {
             // Shortcut for multiplication by zero
-            stack.push_front(&0u128.to_be_bytes()).unwrap();
-            Vec::new()
+            stack.push_value(&0u128)?;
+            Ok(Vec::new())
         } else {
             // Create tasks for initial addition and tracking multiplication progress
-            vec![
+            Ok(vec![
                 Add::new(0, self.x).to_vec_with_type_tag(),
                 MulInternal::new(self.x, self.y, 0, 0).to_vec_with_type_tag(),
-            ]
+            ])
         }
     }
 
@@ -0,0 +1,240 @@
+//! Blake2s-256 over felts or bytes, as used by Stone and Stwo commitments and channels
+//!
+//! The message is pushed in chunks of [`Blake2s::CHUNK_BYTES`] bytes and one chunk is
+//! compressed per step, so long inputs are spread over as many steps as needed.
+
+use std::mem::offset_of;
+
+use utils::{impl_type_identifiable, BidirectionalStack};
+use utils::{pod, Executable, Pod, TaskError, TypeIdentifiable};
+
+use crate::felt::Felt;
+
+const BLOCK_BYTES: usize = 64;
+const DIGEST_BYTES: usize = 32;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Part of the Blake2s-256 digest the task leaves on the stack
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blake2sOutput {
+    /// The whole 32-byte digest
+    Full,
+    /// The 20 least significant bytes of the big-endian digest, the others zeroed
+    ///
+    /// Stone's `blake256_masked160_lsb`.
+    Masked160Lsb,
+    /// The 20 most significant bytes of the big-endian digest, the others zeroed
+    ///
+    /// Stone's `blake256_masked160_msb`.
+    Masked160Msb,
+    /// The 31 least significant bytes of the big-endian digest, so it fits in a felt
+    ///
+    /// Stone's `blake256_masked248_lsb`.
+    Masked248Lsb,
+}
+
+impl Blake2sOutput {
+    fn truncate(self, digest: &mut [u8; DIGEST_BYTES]) {
+        match self {
+            Blake2sOutput::Full => {}
+            Blake2sOutput::Masked160Lsb => digest[..DIGEST_BYTES - 20].fill(0),
+            Blake2sOutput::Masked160Msb => digest[20..].fill(0),
+            Blake2sOutput::Masked248Lsb => digest[..DIGEST_BYTES - 31].fill(0),
+        }
+    }
+}
+
+/// Blake2s-256 of a message taken from the front side in chunks
+///
+/// Felts are hashed as their 32-byte big-endian encodings, one after the other. The
+/// digest is pushed as a 32-byte `Vec<u8>`.
+#[repr(C)]
+pub struct Blake2s {
+    /// Chaining value
+    state: [u32; 8],
+    /// Length of the whole message in bytes
+    length: u64,
+    /// Message bytes compressed so far
+    counter: u64,
+    output: Blake2sOutput,
+}
+
+impl_type_identifiable!(Blake2s, "stark.blake2s", 1);
+
+unsafe impl Pod for Blake2s {
+    /// Reject output discriminants that do not name a [`Blake2sOutput`]
+    fn is_valid_bit_pattern(bytes: &[u8]) -> bool {
+        pod::is_valid_discriminant(
+            bytes,
+            offset_of!(Blake2s, output),
+            Blake2sOutput::Masked248Lsb as u64,
+        )
+    }
+}
+
+impl Blake2s {
+    /// Message bytes compressed by one step
+    pub const CHUNK_BYTES: usize = 16 * BLOCK_BYTES;
+
+    pub fn new(inputs: &[Felt], output: Blake2sOutput) -> Self {
+        Self::with_length(inputs.len() * 32, output)
+    }
+
+    pub fn new_bytes(bytes: &[u8], output: Blake2sOutput) -> Self {
+        Self::with_length(bytes.len(), output)
+    }
+
+    fn with_length(length: usize, output: Blake2sOutput) -> Self {
+        let mut state = IV;
+        // No key and a 32-byte digest
+        state[0] ^= 0x0101_0000 ^ DIGEST_BYTES as u32;
+
+        Self {
+            state,
+            length: length as u64,
+            counter: 0,
+            output,
+        }
+    }
+
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        inputs: &[Felt],
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        let bytes: Vec<u8> = inputs
+            .iter()
+            .flat_map(|input| input.to_bytes_be())
+            .collect();
+        Self::push_bytes(&bytes, stack)
+    }
+
+    pub fn push_bytes<T: BidirectionalStack + ?Sized>(
+        bytes: &[u8],
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        // The first chunk ends up on top, an empty message still takes one step
+        let mut chunks: Vec<&[u8]> = bytes.chunks(Self::CHUNK_BYTES).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        for chunk in chunks.into_iter().rev() {
+            stack.push_value(&chunk.to_vec())?;
+        }
+        Ok(())
+    }
+
+    fn compress(&mut self, block: &[u8], last: bool) {
+        let mut padded = [0; BLOCK_BYTES];
+        padded[..block.len()].copy_from_slice(block);
+        let message: [u32; 16] = std::array::from_fn(|i| {
+            u32::from_le_bytes(padded[4 * i..4 * i + 4].try_into().unwrap())
+        });
+
+        self.counter += block.len() as u64;
+        let mut v = [0; 16];
+        v[..8].copy_from_slice(&self.state);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.counter as u32;
+        v[13] ^= (self.counter >> 32) as u32;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for sigma in &SIGMA {
+            g(&mut v, 0, 4, 8, 12, message[sigma[0]], message[sigma[1]]);
+            g(&mut v, 1, 5, 9, 13, message[sigma[2]], message[sigma[3]]);
+            g(&mut v, 2, 6, 10, 14, message[sigma[4]], message[sigma[5]]);
+            g(&mut v, 3, 7, 11, 15, message[sigma[6]], message[sigma[7]]);
+            g(&mut v, 0, 5, 10, 15, message[sigma[8]], message[sigma[9]]);
+            g(&mut v, 1, 6, 11, 12, message[sigma[10]], message[sigma[11]]);
+            g(&mut v, 2, 7, 8, 13, message[sigma[12]], message[sigma[13]]);
+            g(&mut v, 3, 4, 9, 14, message[sigma[14]], message[sigma[15]]);
+        }
+
+        for (i, word) in self.state.iter_mut().enumerate() {
+            *word ^= v[i] ^ v[i + 8];
+        }
+    }
+
+    fn digest(&self) -> Vec<u8> {
+        let mut digest = [0; DIGEST_BYTES];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        self.output.truncate(&mut digest);
+        digest.to_vec()
+    }
+}
+
+/// Blake2s mixing function
+#[inline(always)]
+fn g(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+impl Executable for Blake2s {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        let chunk: Vec<u8> = stack.pop_value()?;
+
+        // Every chunk but the last is full, the last one ends the message
+        let remaining = self.length.saturating_sub(self.counter);
+        let last = remaining <= Self::CHUNK_BYTES as u64;
+        let expected = if last {
+            remaining
+        } else {
+            Self::CHUNK_BYTES as u64
+        };
+        if chunk.len() as u64 != expected || self.counter > self.length {
+            return Err(
+                TaskError::InvalidState("input chunk does not match the message length").into(),
+            );
+        }
+
+        if last {
+            // The final block is compressed with the last block flag, even when empty
+            let final_start = chunk.len().saturating_sub(1) / BLOCK_BYTES * BLOCK_BYTES;
+            for block in chunk[..final_start].chunks(BLOCK_BYTES) {
+                self.compress(block, false);
+            }
+            self.compress(&chunk[final_start..], true);
+            stack.push_value(&self.digest())?;
+        } else {
+            for block in chunk.chunks(BLOCK_BYTES) {
+                self.compress(block, false);
+            }
+        }
+
+        Ok(vec![])
+    }
+
+    fn is_finished(&mut self) -> bool {
+        self.counter >= self.length
+    }
+}
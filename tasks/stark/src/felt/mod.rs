@@ -4,6 +4,7 @@ use core::ops::{Add, Neg};
 use core::str::FromStr;
 
 use size_of::SizeOf;
+use utils::{check_length, StackValue, ValueError};
 
 use lambdaworks_math::{
     field::{
@@ -416,6 +417,21 @@ impl FromStr for Felt {
     }
 }
 
+/// Stored on the stack as its 32-byte big-endian representation.
+impl StackValue for Felt {
+    const VALUE_TAG: u8 = 0x10;
+    const ENCODED_SIZE: Option<usize> = Some(32);
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_bytes_be());
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, ValueError> {
+        check_length(bytes, 32)?;
+        Ok(Felt::from_bytes_be(bytes.try_into().unwrap()))
+    }
+}
+
 impl Add<&Felt> for u64 {
     type Output = Option<u64>;
 
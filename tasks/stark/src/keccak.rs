@@ -0,0 +1,170 @@
+//! Keccak-256 over data frames, as used by Stone's channel and Merkle commitments
+//!
+//! Hashing goes through `solana_program::keccak`, which calls the `sol_keccak256`
+//! syscall on chain and hashes natively on the host.
+
+use std::mem::offset_of;
+
+use solana_program::keccak::{hashv, HASH_BYTES};
+use utils::{impl_type_identifiable, BidirectionalStack};
+use utils::{pod, Executable, Pod, TypeIdentifiable};
+
+use crate::felt::Felt;
+
+/// Bytes of the digest kept by the masked output modes
+const MASKED_BYTES: usize = 20;
+
+/// Part of the Keccak-256 digest a task leaves on the stack
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeccakOutput {
+    /// The whole 32-byte digest
+    Full,
+    /// The 20 least significant bytes of the big-endian digest, the others zeroed
+    ///
+    /// Stone's `keccak256_masked160_lsb`, used for Merkle commitments.
+    Masked160Lsb,
+    /// The 20 most significant bytes of the big-endian digest, the others zeroed
+    ///
+    /// Stone's `keccak256_masked160_msb`.
+    Masked160Msb,
+}
+
+impl KeccakOutput {
+    /// Keccak-256 of the concatenated parts, masked to this output mode
+    pub fn hash(self, parts: &[&[u8]]) -> Vec<u8> {
+        let mut digest = hashv(parts).to_bytes();
+        match self {
+            KeccakOutput::Full => {}
+            KeccakOutput::Masked160Lsb => digest[..HASH_BYTES - MASKED_BYTES].fill(0),
+            KeccakOutput::Masked160Msb => digest[MASKED_BYTES..].fill(0),
+        }
+        digest.to_vec()
+    }
+}
+
+/// Keccak-256 of `count` felts taken from the front side
+///
+/// Felts are hashed in the order they are popped, each as its 32-byte big-endian
+/// encoding. The digest is pushed as a 32-byte `Vec<u8>`.
+#[repr(C)]
+pub struct KeccakFelts {
+    count: usize,
+    output: KeccakOutput,
+}
+
+impl_type_identifiable!(KeccakFelts, "stark.keccak_felts", 1);
+
+unsafe impl Pod for KeccakFelts {
+    /// Reject output discriminants that do not name a [`KeccakOutput`]
+    fn is_valid_bit_pattern(bytes: &[u8]) -> bool {
+        pod::is_valid_discriminant(
+            bytes,
+            offset_of!(KeccakFelts, output),
+            KeccakOutput::Masked160Msb as u64,
+        )
+    }
+}
+
+impl KeccakFelts {
+    pub fn new(felts: &[Felt], output: KeccakOutput) -> Self {
+        Self {
+            count: felts.len(),
+            output,
+        }
+    }
+
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        felts: &[Felt],
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        // The first felt ends up on top, so it is hashed first
+        for felt in felts.iter().rev() {
+            stack.push_value(felt)?;
+        }
+        Ok(())
+    }
+}
+
+impl Executable for KeccakFelts {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        let mut felts = Vec::new();
+        for _ in 0..self.count {
+            felts.push(stack.pop_value::<Felt>()?.to_bytes_be());
+        }
+        let parts: Vec<&[u8]> = felts.iter().map(|felt| felt.as_slice()).collect();
+
+        stack.push_value(&self.output.hash(&parts))?;
+        Ok(vec![])
+    }
+
+    fn is_finished(&mut self) -> bool {
+        true
+    }
+}
+
+/// Keccak-256 of the concatenation of `count` byte frames taken from the front side
+///
+/// Frames are hashed in the order they are popped, so two digests on top of the stack
+/// hash into their Merkle parent. The digest is pushed as a 32-byte `Vec<u8>`.
+#[repr(C)]
+pub struct KeccakBytes {
+    count: usize,
+    output: KeccakOutput,
+}
+
+impl_type_identifiable!(KeccakBytes, "stark.keccak_bytes", 1);
+
+unsafe impl Pod for KeccakBytes {
+    /// Reject output discriminants that do not name a [`KeccakOutput`]
+    fn is_valid_bit_pattern(bytes: &[u8]) -> bool {
+        pod::is_valid_discriminant(
+            bytes,
+            offset_of!(KeccakBytes, output),
+            KeccakOutput::Masked160Msb as u64,
+        )
+    }
+}
+
+impl KeccakBytes {
+    pub fn new(frames: &[Vec<u8>], output: KeccakOutput) -> Self {
+        Self {
+            count: frames.len(),
+            output,
+        }
+    }
+
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        frames: &[Vec<u8>],
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        // The first frame ends up on top, so it is hashed first
+        for frame in frames.iter().rev() {
+            stack.push_value(frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl Executable for KeccakBytes {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        let mut frames = Vec::new();
+        for _ in 0..self.count {
+            frames.push(stack.pop_value::<Vec<u8>>()?);
+        }
+        let parts: Vec<&[u8]> = frames.iter().map(Vec::as_slice).collect();
+
+        stack.push_value(&self.output.hash(&parts))?;
+        Ok(vec![])
+    }
+
+    fn is_finished(&mut self) -> bool {
+        true
+    }
+}
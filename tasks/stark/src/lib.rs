@@ -1,2 +1,22 @@
+pub mod blake2s;
 pub mod felt;
+pub mod keccak;
+pub mod pedersen;
 pub mod poseidon;
+
+utils::register_tasks! {
+    // Both tasks used to be identified by their module path, with the same layout
+    poseidon::PoseidonHashMany {
+        ("stark::poseidon::PoseidonHashMany", 0) => utils::pod::from_bytes,
+    },
+    poseidon::PoseidonHash,
+    poseidon::PoseidonHashSingle,
+    poseidon::hades::HadesPermutation {
+        ("stark::poseidon::hades::HadesPermutation", 0) => utils::pod::from_bytes,
+    },
+    pedersen::hash::PedersenHash,
+    pedersen::PedersenHashOnElements,
+    keccak::KeccakFelts,
+    keccak::KeccakBytes,
+    blake2s::Blake2s,
+}
@@ -0,0 +1,47 @@
+use lambdaworks_math::elliptic_curve::short_weierstrass::{
+    curves::stark_curve::StarkCurve, point::ShortWeierstrassProjectivePoint,
+};
+
+use crate::pedersen::hash::PedersenHash;
+
+impl PedersenHash {
+    /// Bits of an input multiplied by the low base point
+    pub const N_LOW_BITS: usize = 248;
+    /// Bits of an input multiplied by the high base point
+    pub const N_HIGH_BITS: usize = 4;
+    pub const N_INPUT_BITS: usize = Self::N_LOW_BITS + Self::N_HIGH_BITS;
+
+    /// Input bits processed by one step
+    ///
+    /// Every bit costs a point doubling and, when set, a point addition.
+    pub const BITS_PER_STEP: usize = 8;
+
+    // Points from the specification at
+    // https://docs.starkware.co/starkex/crypto/pedersen-hash-function.html, derived from
+    // the digits of pi.
+    pub const SHIFT_POINT: ShortWeierstrassProjectivePoint<StarkCurve> =
+        StarkCurve::from_affine_hex_string(
+            "0x049ee3eba8c1600700ee1b87eb599f16716b0b1022947733551fde4050ca6804",
+            "0x03ca0cfe4b3bc6ddf346d49d06ea0ed34e621062c0e056c1d0405d266e10268a",
+        );
+
+    /// Base points for the low and high bits of the first input, then of the second one
+    pub const BASE_POINTS: [ShortWeierstrassProjectivePoint<StarkCurve>; 4] = [
+        StarkCurve::from_affine_hex_string(
+            "0x0234287dcbaffe7f969c748655fca9e58fa8120b6d56eb0c1080d17957ebe47b",
+            "0x03b056f100f96fb21e889527d41f4e39940135dd7a6c94cc6ed0268ee89e5615",
+        ),
+        StarkCurve::from_affine_hex_string(
+            "0x04fa56f376c83db33f9dab2656558f3399099ec1de5e3018b7a6932dba8aa378",
+            "0x03fa0984c931c9e38113e0c0e47e4401562761f92a7a23b45168f4e80ff5b54d",
+        ),
+        StarkCurve::from_affine_hex_string(
+            "0x04ba4cc166be8dec764910f75b45f74b40c690c74709e90f3aa372f0bd2d6997",
+            "0x0040301cf5c1751f4b971e46c4ede85fcac5c59a5ce5ae7c48151f27b24b219c",
+        ),
+        StarkCurve::from_affine_hex_string(
+            "0x054302dcb0e6cc1c6e44cca8f61a63bb2ca65048d53fb325d36ff12c49a58202",
+            "0x01b77b3e37d13504b348046268d8ae25ce98ad783c25561a879dcc77e99c2426",
+        ),
+    ];
+}
@@ -0,0 +1,151 @@
+use std::mem::offset_of;
+
+use lambdaworks_math::cyclic_group::IsGroup;
+use lambdaworks_math::elliptic_curve::short_weierstrass::{
+    curves::stark_curve::StarkCurve, point::ShortWeierstrassProjectivePoint,
+};
+use utils::{impl_type_identifiable, BidirectionalStack};
+use utils::{pod, Executable, Pod, TaskError, TypeIdentifiable};
+
+use crate::felt::Felt;
+
+type Point = ShortWeierstrassProjectivePoint<StarkCurve>;
+
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PedersenPhase {
+    LoadInputs,
+    Accumulate,
+    Finished,
+}
+
+/// Starknet Pedersen hash of the `[Felt; 2]` value on top of the front side
+///
+/// The hash is the x coordinate of `SHIFT_POINT + a_low * P1 + a_high * P2 + b_low * P3 +
+/// b_high * P4`, where the low parts are the first 248 bits of each input. The scalar
+/// multiplications run [`Self::BITS_PER_STEP`] bits per step, and the hash is pushed
+/// as a [`Felt`] once all bits are in.
+#[repr(C)]
+pub struct PedersenHash {
+    inputs: [Felt; 2],
+    /// Shift point plus the multiples added so far, in projective coordinates
+    accumulator: [Felt; 3],
+    /// Base point of the current segment, doubled for every bit processed
+    base: [Felt; 3],
+    phase: PedersenPhase,
+    /// Bits of both inputs processed so far
+    bit_index: usize,
+}
+
+impl_type_identifiable!(PedersenHash, "stark.pedersen_hash", 1);
+
+unsafe impl Pod for PedersenHash {
+    /// Reject phase discriminants that do not name a [`PedersenPhase`]
+    fn is_valid_bit_pattern(bytes: &[u8]) -> bool {
+        pod::is_valid_discriminant(
+            bytes,
+            offset_of!(PedersenHash, phase),
+            PedersenPhase::Finished as u64,
+        )
+    }
+}
+
+impl Default for PedersenHash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PedersenHash {
+    pub fn new() -> Self {
+        Self {
+            inputs: [Felt::ZERO; 2],
+            accumulator: [Felt::ZERO; 3],
+            base: [Felt::ZERO; 3],
+            phase: PedersenPhase::LoadInputs,
+            bit_index: 0,
+        }
+    }
+
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        a: Felt,
+        b: Felt,
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        stack.push_value(&[a, b])
+    }
+
+    /// Point stored in the task, checked to be on the curve since the task frame may be forged
+    fn point(coordinates: &[Felt; 3]) -> Result<Point, TaskError> {
+        Point::new(coordinates.map(|coordinate| coordinate.0))
+            .map_err(|_| TaskError::InvalidState("stored point is not on the curve"))
+    }
+
+    fn coordinates(point: &Point) -> [Felt; 3] {
+        point.coordinates().map(Felt)
+    }
+
+    /// Add the multiples of the base points for the next `bits` input bits
+    fn accumulate(&mut self, bits: usize) -> Result<(), TaskError> {
+        let mut accumulator = Self::point(&self.accumulator)?;
+        let mut base = Self::point(&self.base)?;
+        let input_bits = self.inputs.map(|input| input.to_bits_le());
+
+        let end = (self.bit_index + bits).min(2 * Self::N_INPUT_BITS);
+        while self.bit_index < end {
+            let input = self.bit_index / Self::N_INPUT_BITS;
+            let bit = self.bit_index % Self::N_INPUT_BITS;
+
+            // The low and high bits of every input have their own base point
+            if bit == 0 || bit == Self::N_LOW_BITS {
+                base = Self::BASE_POINTS[2 * input + usize::from(bit == Self::N_LOW_BITS)].clone();
+            }
+            if input_bits[input][bit] {
+                accumulator = accumulator.operate_with(&base);
+            }
+            if bit + 1 != Self::N_LOW_BITS && bit + 1 != Self::N_INPUT_BITS {
+                base = base.double();
+            }
+
+            self.bit_index += 1;
+        }
+
+        self.accumulator = Self::coordinates(&accumulator);
+        self.base = Self::coordinates(&base);
+        Ok(())
+    }
+}
+
+impl Executable for PedersenHash {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        if self.phase == PedersenPhase::LoadInputs {
+            self.inputs = stack.pop_value()?;
+            self.accumulator = Self::coordinates(&Self::SHIFT_POINT);
+            self.base = Self::coordinates(&Self::BASE_POINTS[0]);
+            self.bit_index = 0;
+            self.phase = PedersenPhase::Accumulate;
+        }
+
+        if self.phase == PedersenPhase::Accumulate {
+            if self.bit_index >= 2 * Self::N_INPUT_BITS {
+                return Err(TaskError::InvalidState("bit index past the inputs").into());
+            }
+            self.accumulate(Self::BITS_PER_STEP)?;
+
+            if self.bit_index == 2 * Self::N_INPUT_BITS {
+                let hash = Self::point(&self.accumulator)?.to_affine();
+                stack.push_value(&Felt(*hash.x()))?;
+                self.phase = PedersenPhase::Finished;
+            }
+        }
+
+        Ok(vec![])
+    }
+
+    fn is_finished(&mut self) -> bool {
+        self.phase == PedersenPhase::Finished
+    }
+}
@@ -0,0 +1,60 @@
+pub mod constants;
+pub mod hash;
+
+use utils::{impl_type_identifiable, BidirectionalStack, Executable, Pod, TypeIdentifiable};
+
+use crate::{felt::Felt, pedersen::hash::PedersenHash};
+
+/// Pedersen hash chained over a list of elements, as Starknet's `compute_hash_on_elements`
+///
+/// Starting from zero, every element is hashed into the running value, followed by the
+/// number of elements. The hash is left on the stack as a [`Felt`].
+#[repr(C)]
+pub struct PedersenHashOnElements {
+    length: usize,
+    counter: usize,
+}
+
+impl_type_identifiable!(PedersenHashOnElements, "stark.pedersen_hash_on_elements", 1);
+
+unsafe impl Pod for PedersenHashOnElements {}
+
+impl PedersenHashOnElements {
+    pub fn new(elements: &[Felt]) -> Self {
+        Self {
+            length: elements.len(),
+            counter: 0,
+        }
+    }
+
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        elements: &[Felt],
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        // Elements are hashed in order and the length last, topped by the initial zero
+        stack.push_value(&Felt::from(elements.len()))?;
+        for element in elements.iter().rev() {
+            stack.push_value(element)?;
+        }
+        stack.push_value(&Felt::ZERO)
+    }
+}
+
+impl Executable for PedersenHashOnElements {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        let hash: Felt = stack.pop_value()?;
+        let element: Felt = stack.pop_value()?;
+        PedersenHash::push_input(hash, element, stack)?;
+
+        self.counter += 1;
+
+        Ok(vec![PedersenHash::new().to_vec_with_type_tag()])
+    }
+
+    fn is_finished(&mut self) -> bool {
+        self.counter > self.length
+    }
+}
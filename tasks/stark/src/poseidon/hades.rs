@@ -1,7 +1,10 @@
+use std::mem::offset_of;
+
 use crate::felt::Felt;
 use utils::{impl_type_identifiable, BidirectionalStack};
-use utils::{Executable, TypeIdentifiable};
+use utils::{pod, Executable, Pod, TaskError, TypeIdentifiable};
 
+#[repr(u64)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HadesPhase {
     FirstHalfFullRounds,
@@ -16,7 +19,18 @@ pub struct HadesPermutation {
     constants_index: usize,
 }
 
-impl_type_identifiable!(HadesPermutation);
+impl_type_identifiable!(HadesPermutation, "stark.hades", 1);
+
+unsafe impl Pod for HadesPermutation {
+    /// Reject phase discriminants that do not name a [`HadesPhase`]
+    fn is_valid_bit_pattern(bytes: &[u8]) -> bool {
+        pod::is_valid_discriminant(
+            bytes,
+            offset_of!(HadesPermutation, phase),
+            HadesPhase::Finished as u64,
+        )
+    }
+}
 
 impl HadesPermutation {
     pub fn new(state: [Felt; 3]) -> Self {
@@ -27,6 +41,17 @@ impl HadesPermutation {
         }
     }
 
+    /// Index of the first round constant used in the given phase
+    const fn first_constant_index(phase: HadesPhase) -> usize {
+        match phase {
+            HadesPhase::FirstHalfFullRounds => 0,
+            HadesPhase::SecondHalfFullRounds => {
+                Self::N_FULL_ROUNDS / 2 * Self::N_ROUND_CONSTANTS_COLS + Self::N_PARTIAL_ROUNDS / 2
+            }
+            HadesPhase::Finished => Self::ROUND_CONSTANTS.len(),
+        }
+    }
+
     /// Redefined mix function for optimization purposes
     #[inline(always)]
     fn mix(state: &mut [Felt]) {
@@ -60,7 +85,17 @@ impl HadesPermutation {
 }
 
 impl Executable for HadesPermutation {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        // The index comes from the task frame, so an out-of-place one must not reach the constants
+        if self.constants_index != Self::first_constant_index(self.phase) {
+            return Err(
+                TaskError::InvalidState("round constants index does not match the phase").into(),
+            );
+        }
+
         match self.phase {
             HadesPhase::FirstHalfFullRounds => {
                 // First half of full rounds
@@ -90,15 +125,13 @@ impl Executable for HadesPermutation {
 
                     self.constants_index += Self::N_ROUND_CONSTANTS_COLS;
                 }
-                self.state.iter().rev().for_each(|value| {
-                    stack.push_front(&value.to_bytes_be()).unwrap();
-                });
+                stack.push_value(&self.state)?;
                 self.phase = HadesPhase::Finished;
             }
             HadesPhase::Finished => {}
         }
 
-        vec![]
+        Ok(vec![])
     }
 
     fn is_finished(&mut self) -> bool {
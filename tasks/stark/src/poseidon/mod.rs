@@ -1,7 +1,7 @@
 pub mod constants;
 pub mod hades;
 
-use utils::{impl_type_identifiable, BidirectionalStack, Executable, TypeIdentifiable};
+use utils::{impl_type_identifiable, BidirectionalStack, Executable, Pod, TypeIdentifiable};
 
 use crate::{felt::Felt, poseidon::hades::HadesPermutation};
 
@@ -12,7 +12,9 @@ pub struct PoseidonHashMany {
     counter: usize,
 }
 
-impl_type_identifiable!(PoseidonHashMany);
+impl_type_identifiable!(PoseidonHashMany, "stark.poseidon_hash_many", 1);
+
+unsafe impl Pod for PoseidonHashMany {}
 
 impl PoseidonHashMany {
     pub fn new(inputs: &[Felt]) -> Self {
@@ -23,39 +25,32 @@ impl PoseidonHashMany {
         }
     }
 
-    pub fn push_input<T: BidirectionalStack>(inputs: &[Felt], stack: &mut T) {
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        inputs: &[Felt],
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
         // Pad input with 1 followed by 0's (if necessary).
         let mut values = inputs.to_owned();
         values.push(Felt::ONE);
         values.resize(values.len().div_ceil(2) * 2, Felt::ZERO);
 
-        assert!(values.len() % 2 == 0);
+        assert!(values.len().is_multiple_of(2));
 
-        values.iter().rev().for_each(|value| {
-            stack.push_front(&value.to_bytes_be()).unwrap();
-        });
-        stack.push_front(&Felt::ZERO.to_bytes_be()).unwrap();
-        stack.push_front(&Felt::ZERO.to_bytes_be()).unwrap();
-        stack.push_front(&Felt::ZERO.to_bytes_be()).unwrap();
+        // Inputs are absorbed in pairs, topped by the initial zero state
+        for pair in values.chunks_exact(2).rev() {
+            stack.push_value(&[pair[0], pair[1]])?;
+        }
+        stack.push_value(&[Felt::ZERO; 3])
     }
 }
 
 impl Executable for PoseidonHashMany {
-    fn execute<T: BidirectionalStack>(&mut self, stack: &mut T) -> Vec<Vec<u8>> {
-        let s1 = Felt::from_bytes_be(stack.borrow_front().try_into().unwrap());
-        stack.pop_front();
-
-        let s2 = Felt::from_bytes_be(stack.borrow_front().try_into().unwrap());
-        stack.pop_front();
-
-        let s3 = Felt::from_bytes_be(stack.borrow_front().try_into().unwrap());
-        stack.pop_front();
-
-        let v1 = Felt::from_bytes_be(stack.borrow_front().try_into().unwrap());
-        stack.pop_front();
-
-        let v2 = Felt::from_bytes_be(stack.borrow_front().try_into().unwrap());
-        stack.pop_front();
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        let [s1, s2, s3]: [Felt; 3] = stack.pop_value()?;
+        let [v1, v2]: [Felt; 2] = stack.pop_value()?;
 
         self.state[0] = s1 + v1;
         self.state[1] = s2 + v2;
@@ -63,10 +58,118 @@ impl Executable for PoseidonHashMany {
 
         self.counter += 2;
 
-        vec![HadesPermutation::new(self.state).to_vec_with_type_tag()]
+        Ok(vec![
+            HadesPermutation::new(self.state).to_vec_with_type_tag()
+        ])
     }
 
     fn is_finished(&mut self) -> bool {
         self.counter >= self.input_length
     }
 }
+
+/// Starknet's `poseidon_hash(x, y)`, a single permutation of `[x, y, 2]`
+///
+/// The permuted state is left on the stack, the hash being its first element.
+#[repr(C)]
+pub struct PoseidonHash {
+    state: [Felt; 3],
+}
+
+impl_type_identifiable!(PoseidonHash, "stark.poseidon_hash", 1);
+
+unsafe impl Pod for PoseidonHash {}
+
+impl PoseidonHash {
+    pub fn new() -> Self {
+        Self {
+            state: [Felt::ZERO, Felt::ZERO, Felt::TWO],
+        }
+    }
+
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        x: Felt,
+        y: Felt,
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        stack.push_value(&[x, y])
+    }
+}
+
+impl Default for PoseidonHash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Executable for PoseidonHash {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        let [x, y]: [Felt; 2] = stack.pop_value()?;
+
+        self.state[0] = x;
+        self.state[1] = y;
+
+        Ok(vec![
+            HadesPermutation::new(self.state).to_vec_with_type_tag()
+        ])
+    }
+
+    fn is_finished(&mut self) -> bool {
+        true
+    }
+}
+
+/// Starknet's `poseidon_hash_single(x)`, a single permutation of `[x, 0, 1]`
+///
+/// The permuted state is left on the stack, the hash being its first element.
+#[repr(C)]
+pub struct PoseidonHashSingle {
+    state: [Felt; 3],
+}
+
+impl_type_identifiable!(PoseidonHashSingle, "stark.poseidon_hash_single", 1);
+
+unsafe impl Pod for PoseidonHashSingle {}
+
+impl PoseidonHashSingle {
+    pub fn new() -> Self {
+        Self {
+            state: [Felt::ZERO, Felt::ZERO, Felt::ONE],
+        }
+    }
+
+    pub fn push_input<T: BidirectionalStack + ?Sized>(
+        x: Felt,
+        stack: &mut T,
+    ) -> Result<(), T::Error> {
+        stack.push_value(&x)
+    }
+}
+
+impl Default for PoseidonHashSingle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Executable for PoseidonHashSingle {
+    fn execute<T: BidirectionalStack + ?Sized>(
+        &mut self,
+        stack: &mut T,
+    ) -> Result<Vec<Vec<u8>>, T::Error> {
+        let x: Felt = stack.pop_value()?;
+
+        self.state[0] = x;
+
+        Ok(vec![
+            HadesPermutation::new(self.state).to_vec_with_type_tag()
+        ])
+    }
+
+    fn is_finished(&mut self) -> bool {
+        true
+    }
+}